@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use merkle_verifier_core::merkle_patricia::decode_compact;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok((node_type, nibbles)) = decode_compact(data) {
+        assert!(node_type <= 3);
+        assert!(nibbles.iter().all(|&nibble| nibble < 16));
+        assert_eq!(
+            nibbles.len(),
+            (data.len() - 1) * 2 + (node_type & 1) as usize
+        );
+    }
+});
@@ -0,0 +1,34 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use ethereum_types::H256;
+use libfuzzer_sys::fuzz_target;
+use merkle_verifier_core::keccak::keccak256;
+use merkle_verifier_core::merkle_patricia::{verify_eth_proof, StorageProof};
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    address: [u8; 20],
+    account_proof: Vec<Vec<u8>>,
+    storage_proofs: Vec<([u8; 32], Vec<Vec<u8>>)>,
+}
+
+fuzz_target!(|input: Input| {
+    let state_root = input
+        .account_proof
+        .first()
+        .map_or(H256::zero(), |node| keccak256(node));
+    let storage_proofs: Vec<StorageProof> = input
+        .storage_proofs
+        .into_iter()
+        .map(|(key, proof)| StorageProof { key, proof })
+        .collect();
+
+    // Covers `verify_account_proof`, the account decoding and the storage proofs
+    let _ = verify_eth_proof(
+        state_root,
+        input.address,
+        &input.account_proof,
+        &storage_proofs,
+    );
+});
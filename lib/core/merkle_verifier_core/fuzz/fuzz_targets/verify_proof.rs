@@ -0,0 +1,27 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use ethereum_types::H256;
+use libfuzzer_sys::fuzz_target;
+use merkle_verifier_core::keccak::keccak256;
+use merkle_verifier_core::merkle_patricia::verify_proof;
+use merkle_verifier_core::trace::verify_proof_traced;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    key: Vec<u8>,
+    proof: Vec<Vec<u8>>,
+}
+
+fuzz_target!(|input: Input| {
+    // Root the proof in its first node, a random root would stop every input at the first
+    // hash check
+    let root = input
+        .proof
+        .first()
+        .map_or(H256::zero(), |node| keccak256(node));
+
+    let result = verify_proof(root, &input.key, &input.proof);
+    let traced = verify_proof_traced(root, &input.key, &input.proof);
+    assert_eq!(result, traced.map_err(|err| err.error));
+});
@@ -0,0 +1,207 @@
+extern crate alloc;
+use crate::keccak::keccak256;
+use crate::merkle_patricia::ProofError;
+use alloc::vec::Vec;
+use ethereum_types::{Bloom, H160, H256, H64, U256};
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+use serde::{Deserialize, Serialize};
+
+// Number of fields every header has, the fields added by later forks are appended after them
+const LEGACY_FIELD_COUNT: usize = 15;
+
+// Execution layer block header. Fields introduced by a fork are `None` for blocks before it,
+// a field can only be present if all fields before it are present too.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeader {
+    pub parent_hash: H256,
+    pub ommers_hash: H256,
+    pub beneficiary: H160,
+    pub state_root: H256,
+    pub transactions_root: H256,
+    pub receipts_root: H256,
+    pub logs_bloom: Bloom,
+    pub difficulty: U256,
+    pub number: u64,
+    pub gas_limit: u64,
+    pub gas_used: u64,
+    pub timestamp: u64,
+    pub extra_data: Vec<u8>,
+    pub mix_hash: H256,
+    pub nonce: H64,
+    // London
+    pub base_fee_per_gas: Option<U256>,
+    // Shanghai
+    pub withdrawals_root: Option<H256>,
+    // Cancun
+    pub blob_gas_used: Option<u64>,
+    pub excess_blob_gas: Option<u64>,
+    pub parent_beacon_block_root: Option<H256>,
+    // Prague
+    pub requests_hash: Option<H256>,
+}
+
+impl BlockHeader {
+    // Block hash: keccak256 of the RLP encoded header
+    pub fn hash(&self) -> H256 {
+        header_hash(&rlp::encode(self))
+    }
+
+    // Number of optional fork fields that are present
+    fn fork_field_count(&self) -> usize {
+        [
+            self.base_fee_per_gas.is_some(),
+            self.withdrawals_root.is_some(),
+            self.blob_gas_used.is_some(),
+            self.excess_blob_gas.is_some(),
+            self.parent_beacon_block_root.is_some(),
+            self.requests_hash.is_some(),
+        ]
+        .iter()
+        .take_while(|present| **present)
+        .count()
+    }
+}
+
+impl Encodable for BlockHeader {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        let fork_fields = self.fork_field_count();
+        s.begin_list(LEGACY_FIELD_COUNT + fork_fields);
+        s.append(&self.parent_hash);
+        s.append(&self.ommers_hash);
+        s.append(&self.beneficiary);
+        s.append(&self.state_root);
+        s.append(&self.transactions_root);
+        s.append(&self.receipts_root);
+        s.append(&self.logs_bloom);
+        s.append(&self.difficulty);
+        s.append(&self.number);
+        s.append(&self.gas_limit);
+        s.append(&self.gas_used);
+        s.append(&self.timestamp);
+        s.append(&self.extra_data);
+        s.append(&self.mix_hash);
+        s.append(&self.nonce);
+
+        // Fields after a missing one are not encoded, so the encoding stays decodable
+        if fork_fields > 0 {
+            s.append(&self.base_fee_per_gas.unwrap_or_default());
+        }
+        if fork_fields > 1 {
+            s.append(&self.withdrawals_root.unwrap_or_default());
+        }
+        if fork_fields > 2 {
+            s.append(&self.blob_gas_used.unwrap_or_default());
+        }
+        if fork_fields > 3 {
+            s.append(&self.excess_blob_gas.unwrap_or_default());
+        }
+        if fork_fields > 4 {
+            s.append(&self.parent_beacon_block_root.unwrap_or_default());
+        }
+        if fork_fields > 5 {
+            s.append(&self.requests_hash.unwrap_or_default());
+        }
+    }
+}
+
+// Decode a fork field, `None` if the header predates the fork
+fn optional_at<T: Decodable>(rlp: &Rlp, index: usize) -> Result<Option<T>, DecoderError> {
+    if index < rlp.item_count()? {
+        Ok(Some(rlp.val_at(index)?))
+    } else {
+        Ok(None)
+    }
+}
+
+impl Decodable for BlockHeader {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        let field_count = rlp.item_count()?;
+        if !(LEGACY_FIELD_COUNT..=LEGACY_FIELD_COUNT + 6).contains(&field_count) {
+            return Err(DecoderError::RlpIncorrectListLen);
+        }
+
+        Ok(BlockHeader {
+            parent_hash: rlp.val_at(0)?,
+            ommers_hash: rlp.val_at(1)?,
+            beneficiary: rlp.val_at(2)?,
+            state_root: rlp.val_at(3)?,
+            transactions_root: rlp.val_at(4)?,
+            receipts_root: rlp.val_at(5)?,
+            logs_bloom: rlp.val_at(6)?,
+            difficulty: rlp.val_at(7)?,
+            number: rlp.val_at(8)?,
+            gas_limit: rlp.val_at(9)?,
+            gas_used: rlp.val_at(10)?,
+            timestamp: rlp.val_at(11)?,
+            extra_data: rlp.val_at(12)?,
+            mix_hash: rlp.val_at(13)?,
+            nonce: rlp.val_at(14)?,
+            base_fee_per_gas: optional_at(rlp, 15)?,
+            withdrawals_root: optional_at(rlp, 16)?,
+            blob_gas_used: optional_at(rlp, 17)?,
+            excess_blob_gas: optional_at(rlp, 18)?,
+            parent_beacon_block_root: optional_at(rlp, 19)?,
+            requests_hash: optional_at(rlp, 20)?,
+        })
+    }
+}
+
+// Block hash of an RLP encoded header
+pub fn header_hash(raw_header: &[u8]) -> H256 {
+    keccak256(raw_header)
+}
+
+// Decode an RLP encoded header. The whole input has to be a single RLP list.
+pub fn decode_header(raw_header: &[u8]) -> Result<BlockHeader, ProofError> {
+    let rlp = Rlp::new(raw_header);
+    if !rlp.is_list() || rlp.payload_info()?.total() != raw_header.len() {
+        return Err(ProofError::RlpDecoding(
+            "Invalid block header encoding".into(),
+        ));
+    }
+
+    Ok(rlp.as_val()?)
+}
+
+// Verify that the first header is an ancestor of the trusted checkpoint and return it.
+//
+// `raw_headers` are RLP encoded headers ordered from the proven block up to the checkpoint
+// block: every header has to be the parent of the next one and the last header has to hash
+// to `checkpoint_hash`. A single header proves the checkpoint block itself.
+pub fn verify_header_chain(
+    raw_headers: &[Vec<u8>],
+    checkpoint_hash: H256,
+) -> Result<BlockHeader, ProofError> {
+    let Some(first) = raw_headers.first() else {
+        return Err(ProofError::InvalidProof("Empty header chain".into()));
+    };
+
+    let proven_header = decode_header(first)?;
+    let mut previous = (header_hash(first), proven_header.number);
+
+    for raw_header in &raw_headers[1..] {
+        let header = decode_header(raw_header)?;
+
+        let (parent_hash, parent_number) = previous;
+        if header.parent_hash != parent_hash {
+            return Err(ProofError::HashMismatch(
+                "Header chain: parent hash mismatch".into(),
+            ));
+        }
+        if Some(header.number) != parent_number.checked_add(1) {
+            return Err(ProofError::InvalidProof(
+                "Header chain: non consecutive block numbers".into(),
+            ));
+        }
+
+        previous = (header_hash(raw_header), header.number);
+    }
+
+    if previous.0 != checkpoint_hash {
+        return Err(ProofError::HashMismatch(
+            "Header chain does not end at the checkpoint".into(),
+        ));
+    }
+
+    Ok(proven_header)
+}
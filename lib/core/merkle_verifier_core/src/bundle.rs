@@ -0,0 +1,92 @@
+extern crate alloc;
+use crate::merkle_patricia::{AccountData, ProofError, StorageProof, StorageValues};
+use crate::multiproof::{verify_account_storage, NodeCache};
+use alloc::vec::Vec;
+use ethereum_types::H256;
+use serde::{Deserialize, Serialize};
+
+// Account proof together with the proofs of the requested storage slots, as returned by
+// `eth_getProof`. This is the input schema shared by hosts and guests.
+//
+// Only the proof nodes are carried over: the balance, nonce and storage values claimed by
+// the RPC response are not part of the bundle, the guest learns them from the proof.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountProofBundle {
+    pub address: [u8; 20],
+    pub account_proof: Vec<Vec<u8>>,
+    pub storage_proofs: Vec<StorageProof>,
+}
+
+impl AccountProofBundle {
+    // Verify the account and all its storage slots against the state root.
+    //
+    // The nodes of all proofs are checked together in one `NodeCache`: the storage proofs
+    // share their upper nodes, which are hashed once. Which proof of the bundle lists a
+    // node does not matter, the framed encoding decodes a bundle as a single node table.
+    pub fn verify(
+        &self,
+        state_root: H256,
+    ) -> Result<(Option<AccountData>, StorageValues), ProofError> {
+        let proofs = core::iter::once(self.account_proof.as_slice()).chain(
+            self.storage_proofs
+                .iter()
+                .map(|storage| storage.proof.as_slice()),
+        );
+        let slots: Vec<[u8; 32]> = self
+            .storage_proofs
+            .iter()
+            .map(|storage| storage.key)
+            .collect();
+
+        verify_account_storage(
+            &NodeCache::from_proofs(proofs),
+            state_root,
+            &self.address,
+            &slots,
+        )
+    }
+}
+
+#[cfg(feature = "ethers")]
+mod ethers_conversion {
+    use super::AccountProofBundle;
+    use crate::merkle_patricia::StorageProof;
+    use ethers_core::types::{EIP1186ProofResponse, StorageProof as EthersStorageProof};
+
+    impl From<&EthersStorageProof> for StorageProof {
+        fn from(storage_proof: &EthersStorageProof) -> Self {
+            StorageProof {
+                key: storage_proof.key.into(),
+                proof: storage_proof
+                    .proof
+                    .iter()
+                    .map(|node| node.to_vec())
+                    .collect(),
+            }
+        }
+    }
+
+    impl From<&EIP1186ProofResponse> for AccountProofBundle {
+        fn from(response: &EIP1186ProofResponse) -> Self {
+            AccountProofBundle {
+                address: response.address.into(),
+                account_proof: response
+                    .account_proof
+                    .iter()
+                    .map(|node| node.to_vec())
+                    .collect(),
+                storage_proofs: response
+                    .storage_proof
+                    .iter()
+                    .map(StorageProof::from)
+                    .collect(),
+            }
+        }
+    }
+
+    impl From<EIP1186ProofResponse> for AccountProofBundle {
+        fn from(response: EIP1186ProofResponse) -> Self {
+            AccountProofBundle::from(&response)
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use crate::keccak::keccak256;
+use crate::merkle_patricia::{AccountData, ProofError};
+use ethereum_types::H256;
+
+// keccak256 of the empty code, the code hash of every account without code
+pub const EMPTY_CODE_HASH: H256 = H256([
+    0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+    0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+]);
+
+// Check that `code` is the runtime bytecode of the proven account and, with an allowlist,
+// that it is one of the known deployments (e.g. the audited Lending contract). Returns the
+// code hash.
+pub fn verify_code(
+    account: &AccountData,
+    code: &[u8],
+    allowlist: Option<&[H256]>,
+) -> Result<H256, ProofError> {
+    let code_hash = keccak256(code);
+    if code_hash != account.code_hash {
+        return Err(ProofError::HashMismatch(
+            "Code does not match the account code hash".into(),
+        ));
+    }
+
+    if let Some(allowlist) = allowlist {
+        if !allowlist.contains(&code_hash) {
+            return Err(ProofError::InvalidProof(
+                "Contract code is not allowlisted".into(),
+            ));
+        }
+    }
+
+    Ok(code_hash)
+}
+
+impl AccountData {
+    // Account without code, i.e. controlled by a private key only. Accounts delegating to
+    // a contract (EIP-7702) hold the delegation designator as code, so they are not.
+    pub fn is_eoa(&self) -> bool {
+        self.code_hash == EMPTY_CODE_HASH
+    }
+}
+
+// Require a proven account to be a plain externally owned account
+pub fn verify_eoa(account: &AccountData) -> Result<(), ProofError> {
+    if !account.is_eoa() {
+        return Err(ProofError::InvalidProof("Account has code".into()));
+    }
+    Ok(())
+}
@@ -0,0 +1,217 @@
+extern crate alloc;
+use crate::bundle::AccountProofBundle;
+use crate::merkle_patricia::{ProofError, StorageProof};
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+// Compact binary encoding of the guest inputs.
+//
+// `env::read()` deserializes with the risc0 codec, which spends a 32 bit word on every
+// byte of a `Vec<u8>`: a proof is read as four times its size, one word at a time. The
+// framed encoding is read as one byte buffer with `env::read_slice` and decoded from
+// memory. All integers are little endian `u32`, byte strings are prefixed by their length.
+//
+// A bundle stores every distinct node of its proofs once, in a node table, followed by
+// the keys of its storage slots. The storage proofs of one account share their upper
+// nodes, and `AccountProofBundle::verify` checks all nodes of a bundle together, so the
+// proofs themselves need not be encoded.
+//
+//   bundle  = address[20] nodes storage_count key[32]*
+//   nodes   = count (length bytes)*
+
+// How a guest input is encoded, announced by the first word the host writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputEncoding {
+    // risc0 serde codec, read with `env::read()`
+    Serde,
+    // Length prefixed frame in the encoding below, read with `env::read_slice`
+    Framed,
+}
+
+impl InputEncoding {
+    pub fn tag(self) -> u32 {
+        match self {
+            InputEncoding::Serde => 0,
+            InputEncoding::Framed => 1,
+        }
+    }
+
+    pub fn from_tag(tag: u32) -> Option<Self> {
+        match tag {
+            0 => Some(InputEncoding::Serde),
+            1 => Some(InputEncoding::Framed),
+            _ => None,
+        }
+    }
+}
+
+// Writes the framed encoding
+#[derive(Debug, Clone, Default)]
+pub struct FrameWriter {
+    buf: Vec<u8>,
+}
+
+impl FrameWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn u32(&mut self, value: u32) -> &mut Self {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn len(&mut self, len: usize) -> &mut Self {
+        self.u32(u32::try_from(len).expect("Framed input item longer than 4 GiB"))
+    }
+
+    // Raw bytes of a length both sides know, like a hash or an address
+    pub fn fixed(&mut self, bytes: &[u8]) -> &mut Self {
+        self.buf.extend_from_slice(bytes);
+        self
+    }
+
+    // Length prefixed byte string
+    pub fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.len(bytes.len()).fixed(bytes)
+    }
+
+    pub fn list(&mut self, items: &[Vec<u8>]) -> &mut Self {
+        self.len(items.len());
+        for item in items {
+            self.bytes(item);
+        }
+        self
+    }
+
+    pub fn bundle(&mut self, bundle: &AccountProofBundle) -> &mut Self {
+        // Every distinct node in the order it first appears
+        let mut seen = BTreeSet::new();
+        let nodes: Vec<&[u8]> = core::iter::once(&bundle.account_proof)
+            .chain(bundle.storage_proofs.iter().map(|storage| &storage.proof))
+            .flatten()
+            .map(Vec::as_slice)
+            .filter(|node| seen.insert(*node))
+            .collect();
+
+        self.fixed(&bundle.address).len(nodes.len());
+        for node in nodes {
+            self.bytes(node);
+        }
+
+        self.len(bundle.storage_proofs.len());
+        for storage in &bundle.storage_proofs {
+            self.fixed(&storage.key);
+        }
+        self
+    }
+
+    pub fn finish(&mut self) -> Vec<u8> {
+        core::mem::take(&mut self.buf)
+    }
+}
+
+// Reads the framed encoding. Every read checks the remaining input, a malformed frame is
+// an error and never a panic, and no byte string is larger than the input.
+#[derive(Debug, Clone)]
+pub struct FrameReader<'a> {
+    data: &'a [u8],
+}
+
+fn truncated() -> ProofError {
+    ProofError::InvalidProof("Truncated framed input".into())
+}
+
+impl<'a> FrameReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        FrameReader { data }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ProofError> {
+        if len > self.data.len() {
+            return Err(truncated());
+        }
+        let (head, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(head)
+    }
+
+    pub fn u32(&mut self) -> Result<u32, ProofError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    // Number of items that follow, each one at least `min_size` bytes long
+    fn count(&mut self, min_size: usize) -> Result<usize, ProofError> {
+        let count = self.u32()? as usize;
+        if count.saturating_mul(min_size) > self.data.len() {
+            return Err(truncated());
+        }
+        Ok(count)
+    }
+
+    pub fn fixed<const N: usize>(&mut self) -> Result<[u8; N], ProofError> {
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(self.take(N)?);
+        Ok(bytes)
+    }
+
+    pub fn bytes(&mut self) -> Result<&'a [u8], ProofError> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    pub fn list(&mut self) -> Result<Vec<Vec<u8>>, ProofError> {
+        let count = self.count(4)?;
+        (0..count)
+            .map(|_| self.bytes().map(<[u8]>::to_vec))
+            .collect()
+    }
+
+    // The node table is decoded as the account proof, and the storage slots come without
+    // nodes of their own. `AccountProofBundle::verify` checks all nodes of a bundle
+    // together, so the decoded bundle proves the same values as the encoded one while
+    // every node is copied and hashed once.
+    pub fn bundle(&mut self) -> Result<AccountProofBundle, ProofError> {
+        let address = self.fixed::<20>()?;
+        let account_proof = self.list()?;
+
+        let storage_count = self.count(32)?;
+        let storage_proofs = (0..storage_count)
+            .map(|_| {
+                Ok(StorageProof {
+                    key: self.fixed::<32>()?,
+                    proof: Vec::new(),
+                })
+            })
+            .collect::<Result<Vec<_>, ProofError>>()?;
+
+        Ok(AccountProofBundle {
+            address,
+            account_proof,
+            storage_proofs,
+        })
+    }
+
+    // The whole input has to be consumed
+    pub fn finish(self) -> Result<(), ProofError> {
+        match self.data.is_empty() {
+            true => Ok(()),
+            false => Err(ProofError::InvalidProof(
+                "Framed input: trailing bytes".into(),
+            )),
+        }
+    }
+}
+
+// Framed encoding of a single bundle
+pub fn encode_bundle(bundle: &AccountProofBundle) -> Vec<u8> {
+    FrameWriter::new().bundle(bundle).finish()
+}
+
+pub fn decode_bundle(data: &[u8]) -> Result<AccountProofBundle, ProofError> {
+    let mut reader = FrameReader::new(data);
+    let bundle = reader.bundle()?;
+    reader.finish()?;
+    Ok(bundle)
+}
@@ -0,0 +1,23 @@
+use ethereum_types::H256;
+
+// Every hash in this crate goes through `keccak256`. With the `zkvm-keccak` feature it is
+// computed by `tiny-keccak`, which guests patch to the RISC Zero fork that uses the keccak
+// accelerator of the zkVM (see the guest's `[patch.crates-io]`). Hosts use `sha3`.
+
+#[cfg(feature = "zkvm-keccak")]
+pub fn keccak256(data: &[u8]) -> H256 {
+    use tiny_keccak::{Hasher, Keccak};
+
+    let mut hasher = Keccak::v256();
+    let mut output = [0u8; 32];
+    hasher.update(data);
+    hasher.finalize(&mut output);
+    H256(output)
+}
+
+#[cfg(not(feature = "zkvm-keccak"))]
+pub fn keccak256(data: &[u8]) -> H256 {
+    use sha3::{Digest, Keccak256};
+
+    H256(Keccak256::digest(data).into())
+}
@@ -1 +1,17 @@
+pub mod block_header;
+pub mod bundle;
+pub mod code;
+pub mod framed;
+pub mod keccak;
 pub mod merkle_patricia;
+pub mod multiproof;
+pub mod price_feed;
+pub mod receipt;
+pub mod staking;
+pub mod storage_layout;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+pub mod token;
+pub mod trace;
+pub mod transaction;
+pub mod wallet_age;
@@ -1,18 +1,21 @@
 extern crate alloc;
+use crate::keccak::keccak256;
+use crate::trace::{NodeKind, ProofStep};
+use alloc::collections::BTreeMap;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use ethereum_types::{H256, U256};
 use rlp::{DecoderError, Rlp};
 use serde::{Deserialize, Serialize};
-use sha3::{Digest, Keccak256};
 
 // Custom error type that can convert from both DecoderError and &str
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ProofError {
     RlpDecoding(String),
     InvalidProof(String),
     HashMismatch(String),
     InvalidPath(String),
+    InvalidValue(String),
 }
 
 // Implement conversion from DecoderError to ProofError
@@ -55,38 +58,159 @@ impl<'a> NibbleSlice<'a> {
         NibbleSlice(data)
     }
 
-    pub fn at(&self, i: usize) -> u8 {
-        if i % 2 == 0 {
-            self.0[i / 2] >> 4
-        } else {
-            self.0[i / 2] & 0x0f
+    // `None` past the end of the slice
+    pub fn at(&self, i: usize) -> Option<u8> {
+        let byte = self.0.get(i / 2)?;
+        match i % 2 {
+            0 => Some(byte >> 4),
+            _ => Some(byte & 0x0f),
         }
     }
 
     pub fn len(&self) -> usize {
         self.0.len() * 2
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 }
 
-// Basic proof verification function
-pub fn verify_proof(
+// Reference from a parent node to one of its children. Nodes whose RLP encoding is
+// shorter than 32 bytes are embedded in their parent instead of being referenced by hash,
+// such nodes are not part of the proof.
+enum NodeRef {
+    Hash(H256),
+    Inline(Vec<u8>),
+}
+
+// Decode a branch child or an extension target
+fn decode_node_ref(item: &Rlp) -> Result<NodeRef, ProofError> {
+    if item.is_list() {
+        let raw = item.as_raw();
+        if raw.len() >= 32 {
+            return Err(ProofError::InvalidProof(
+                "Invalid proof: embedded node too large".into(),
+            ));
+        }
+        return Ok(NodeRef::Inline(raw.to_vec()));
+    }
+
+    let data = item.data()?;
+    if data.len() != 32 {
+        return Err(ProofError::InvalidProof(
+            "Invalid proof: invalid node reference".into(),
+        ));
+    }
+    Ok(NodeRef::Hash(H256::from_slice(data)))
+}
+
+// keccak256(rlp("")), root hash of a trie without any entries
+pub const EMPTY_TRIE_ROOT: H256 = H256([
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+]);
+
+// Longest path a proof can follow: one node per nibble of a 32 byte hashed key, plus the
+// leaf. Keys of the transaction and receipt tries are shorter.
+pub const MAX_PROOF_DEPTH: usize = 65;
+
+// Largest proof node accepted. Branches and extensions are at most 532 bytes, but leaves of
+// the transaction and receipt tries hold whole transactions and receipts, which the block
+// size limit of EIP-7934 keeps below 8 MiB.
+pub const MAX_NODE_SIZE: usize = 8 << 20;
+
+// Why the key is not part of the trie
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DivergenceReason {
+    // The trie has no entries at all
+    EmptyTrie,
+    // The branch has no child for the next nibble of the key
+    EmptyBranchChild { nibble: u8 },
+    // The key ends at a branch that holds no value
+    MissingBranchValue,
+    // The key leads to a leaf holding a different key
+    LeafPathMismatch,
+    // The key leaves the shared path of an extension
+    ExtensionPathMismatch,
+}
+
+// Node at which the path of a key leaves the trie
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Divergence {
+    // RLP encoding of the node, `None` for an empty trie proven by an empty proof
+    pub node: Option<Vec<u8>>,
+    // Number of key nibbles matched before reaching the node
+    pub depth: usize,
+    pub reason: DivergenceReason,
+}
+
+// Outcome of a trie proof verification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofVerification {
+    // The key is in the trie, holding the RLP encoded value
+    Inclusion(Vec<u8>),
+    // The proof shows that the key is not in the trie
+    Exclusion(Divergence),
+    // The proof does not prove anything about the key
+    Invalid(ProofError),
+}
+
+impl ProofVerification {
+    // Value for proven inclusion, `None` for proven exclusion
+    pub fn into_result(self) -> Result<Option<Vec<u8>>, ProofError> {
+        match self {
+            ProofVerification::Inclusion(value) => Ok(Some(value)),
+            ProofVerification::Exclusion(_) => Ok(None),
+            ProofVerification::Invalid(err) => Err(err),
+        }
+    }
+
+    pub fn is_inclusion(&self) -> bool {
+        matches!(self, ProofVerification::Inclusion(_))
+    }
+
+    pub fn is_exclusion(&self) -> bool {
+        matches!(self, ProofVerification::Exclusion(_))
+    }
+}
+
+// Verify a proof for `key` against `root_hash`. Every node of the proof has to be used on
+// the path of the key, otherwise the proof is invalid.
+pub fn verify_trie_proof(root_hash: H256, key: &[u8], proof: &[Vec<u8>]) -> ProofVerification {
+    verify_sequential_proof(root_hash, key, proof, None)
+}
+
+// `verify_trie_proof`, recording the visited nodes in `trace` if given
+pub(crate) fn verify_sequential_proof(
     root_hash: H256,
     key: &[u8],
     proof: &[Vec<u8>],
-) -> Result<Option<Vec<u8>>, ProofError> {
-    // Convert key to nibbles for trie traversal
-    let key_nibbles = encode_path(key);
+    trace: Option<&mut Vec<ProofStep>>,
+) -> ProofVerification {
+    // Reject oversized proofs before hashing anything
+    if proof.len() > MAX_PROOF_DEPTH {
+        return ProofVerification::Invalid(ProofError::InvalidProof(
+            "Proof exceeds the maximum depth".into(),
+        ));
+    }
+    if proof.iter().any(|node| node.len() > MAX_NODE_SIZE) {
+        return ProofVerification::Invalid(ProofError::InvalidProof(
+            "Proof node exceeds the maximum size".into(),
+        ));
+    }
 
-    // Start with the root hash as our expected hash
-    let mut expected_hash = root_hash;
+    let mut proof_nodes = proof.iter();
 
-    // Current position in the path
-    let mut key_index = 0;
+    // Proof nodes are ordered from the root, each one has to hash to the reference the
+    // previous node holds
+    let mut next_proof_node = |expected_hash: H256| {
+        let Some(node_data) = proof_nodes.next() else {
+            return Ok(None);
+        };
 
-    // Process each node in the proof
-    for node_data in proof {
         // Calculate actual hash of this node for verification
-        let actual_hash = H256::from_slice(&Keccak256::digest(node_data).as_slice());
+        let actual_hash = keccak256(node_data);
 
         // Verify this node matches what we expect
         if actual_hash != expected_hash {
@@ -94,33 +218,190 @@ pub fn verify_proof(
                 "Invalid proof: hash mismatch".into(),
             ));
         }
+        Ok(Some(node_data.as_slice()))
+    };
+
+    let verification = match walk_proof(root_hash, key, &mut next_proof_node, trace) {
+        Ok(verification) => verification,
+        Err(err) => return ProofVerification::Invalid(err),
+    };
+
+    if proof_nodes.next().is_some() {
+        return ProofVerification::Invalid(ProofError::InvalidProof(
+            "Proof contains unused nodes".into(),
+        ));
+    }
+
+    verification
+}
+
+// Basic proof verification function, `Ok(None)` means the key is proven to be absent
+pub fn verify_proof(
+    root_hash: H256,
+    key: &[u8],
+    proof: &[Vec<u8>],
+) -> Result<Option<Vec<u8>>, ProofError> {
+    verify_trie_proof(root_hash, key, proof).into_result()
+}
+
+// Check the hex prefix flags of a leaf or extension path
+fn validate_compact_path(path: &[u8]) -> Result<(), ProofError> {
+    let Some(&first) = path.first() else {
+        return Err(ProofError::InvalidPath("Empty node path".into()));
+    };
+
+    match first >> 4 {
+        // Even number of nibbles, the low nibble of the first byte is padding
+        0x0 | 0x2 if first & 0x0f != 0 => {
+            Err(ProofError::InvalidPath("Invalid node path padding".into()))
+        }
+        0x0..=0x3 => Ok(()),
+        _ => Err(ProofError::InvalidPath("Invalid node type".into())),
+    }
+}
+
+// Record what the last visited node is and which key nibbles it consumed
+fn trace_node(trace: &mut Option<&mut Vec<ProofStep>>, kind: NodeKind, nibbles: &[u8]) {
+    if let Some(step) = trace.as_deref_mut().and_then(|steps| steps.last_mut()) {
+        step.kind = Some(kind);
+        step.nibbles = nibbles.to_vec();
+    }
+}
+
+// Follow the path of the key through the trie. `node_by_hash` provides the node referenced
+// by a hash, it has to check that the node hashes to it and returns `None` if the node is
+// not part of the proof. With a `trace`, every visited node is recorded in it.
+pub(crate) fn walk_proof<'a>(
+    root_hash: H256,
+    key: &[u8],
+    node_by_hash: &mut impl FnMut(H256) -> Result<Option<&'a [u8]>, ProofError>,
+    mut trace: Option<&mut Vec<ProofStep>>,
+) -> Result<ProofVerification, ProofError> {
+    // Convert key to nibbles for trie traversal
+    let key_nibbles = encode_path(key);
+
+    // Start with the root hash as our expected node
+    let mut next_node = NodeRef::Hash(root_hash);
+    let mut is_root = true;
+
+    // Current position in the path
+    let mut key_index = 0;
+    // Number of nodes requested from `node_by_hash`, the position in a sequential proof
+    let mut hashed_nodes = 0;
+    // Number of nodes visited, embedded ones included
+    let mut depth = 0;
+
+    loop {
+        let at_root = is_root;
+        is_root = false;
+
+        depth += 1;
+        if depth > MAX_PROOF_DEPTH {
+            return Err(ProofError::InvalidProof(
+                "Proof exceeds the maximum depth".into(),
+            ));
+        }
+
+        if let Some(steps) = trace.as_deref_mut() {
+            let (proof_index, expected_hash) = match next_node {
+                NodeRef::Hash(hash) => (Some(hashed_nodes), Some(hash)),
+                NodeRef::Inline(_) => (None, None),
+            };
+            steps.push(ProofStep {
+                proof_index,
+                kind: None,
+                depth: key_index,
+                nibbles: Vec::new(),
+                expected_hash,
+                actual_hash: None,
+            });
+        }
+
+        let inline_node;
+        let node_data: &[u8] = match next_node {
+            NodeRef::Hash(expected_hash) => {
+                hashed_nodes += 1;
+                let Some(node_data) = node_by_hash(expected_hash)? else {
+                    // An empty trie can be proven without any node
+                    if at_root && expected_hash == EMPTY_TRIE_ROOT {
+                        trace_node(&mut trace, NodeKind::Empty, &[]);
+                        return Ok(ProofVerification::Exclusion(Divergence {
+                            node: None,
+                            depth: 0,
+                            reason: DivergenceReason::EmptyTrie,
+                        }));
+                    }
+                    return Err(ProofError::InvalidProof("Proof too short".into()));
+                };
+
+                if node_data.len() > MAX_NODE_SIZE {
+                    return Err(ProofError::InvalidProof(
+                        "Proof node exceeds the maximum size".into(),
+                    ));
+                }
+                // Nodes shorter than 32 bytes are always embedded, only the root is hashed
+                if !at_root && node_data.len() < 32 {
+                    return Err(ProofError::InvalidProof(
+                        "Invalid proof: node should be embedded".into(),
+                    ));
+                }
+                node_data
+            }
+            // Embedded nodes are covered by the hash of their parent
+            NodeRef::Inline(data) => {
+                inline_node = data;
+                &inline_node
+            }
+        };
 
         // Parse the RLP-encoded node
         let rlp = Rlp::new(node_data);
 
+        // The root of an empty trie is the empty string
+        if at_root && rlp.is_data() && rlp.is_empty() {
+            trace_node(&mut trace, NodeKind::Empty, &[]);
+            return Ok(ProofVerification::Exclusion(Divergence {
+                node: Some(node_data.to_vec()),
+                depth: 0,
+                reason: DivergenceReason::EmptyTrie,
+            }));
+        }
+
+        let exclusion = |reason| {
+            Ok(ProofVerification::Exclusion(Divergence {
+                node: Some(node_data.to_vec()),
+                depth: key_index,
+                reason,
+            }))
+        };
+
         // Branch node (has 17 items: 16 children + value)
         if rlp.item_count()? == 17 {
+            trace_node(&mut trace, NodeKind::Branch, &[]);
+
             // We've consumed the entire path - return the value at this branch
             if key_index >= key_nibbles.len() {
                 let value_item = rlp.at(16)?;
                 if value_item.is_empty() {
-                    return Ok(None); // No value at this node
+                    return exclusion(DivergenceReason::MissingBranchValue);
                 } else {
-                    return Ok(Some(value_item.as_val()?));
+                    return Ok(ProofVerification::Inclusion(value_item.as_val()?));
                 }
             }
 
             // Otherwise, get the next nibble from our key and follow that branch
-            let nibble = key_nibbles[key_index] as usize;
-            key_index += 1;
+            let nibble = key_nibbles[key_index];
 
-            let child = rlp.at(nibble)?;
+            let child = rlp.at(nibble as usize)?;
             if child.is_empty() {
-                return Ok(None); // No child here means no value exists
+                // No child here means no value exists
+                return exclusion(DivergenceReason::EmptyBranchChild { nibble });
             }
+            key_index += 1;
+            trace_node(&mut trace, NodeKind::Branch, &[nibble]);
 
-            // Extract the next hash to follow
-            expected_hash = child.as_val()?;
+            // Extract the next node to follow
+            next_node = decode_node_ref(&child)?;
         }
         // Leaf or extension node (has 2 items)
         else if rlp.item_count()? == 2 {
@@ -128,41 +409,49 @@ pub fn verify_proof(
             let path: Vec<u8> = path_item.as_val()?;
 
             // Decode the compact encoding to get the node type and actual path
-            let (node_type, node_path) = decode_compact(path.as_slice());
+            let (node_type, node_path) = decode_compact(path.as_slice())?;
 
+            // Flags 0/1 mark an extension and 2/3 a leaf, with an even/odd path length
             match node_type {
                 // Leaf node - terminal node with a value
-                0x2 => {
+                0x2 | 0x3 => {
+                    trace_node(&mut trace, NodeKind::Leaf, &[]);
+
                     // Check remaining key matches node path
                     let remaining_key = &key_nibbles[key_index..];
                     if remaining_key != node_path.as_slice() {
-                        return Ok(None); // Path doesn't match our key
+                        return exclusion(DivergenceReason::LeafPathMismatch);
                     }
 
+                    trace_node(&mut trace, NodeKind::Leaf, &node_path);
+
                     // Return the value
                     let value_item = rlp.at(1)?;
-                    return Ok(Some(value_item.as_val()?));
+                    return Ok(ProofVerification::Inclusion(value_item.as_val()?));
                 }
 
                 // Extension node - internal node that compresses shared path
-                0x1 => {
-                    // Check that the path matches our key segment
-                    let path_len = node_path.len();
-                    if key_index + path_len > key_nibbles.len() {
-                        return Ok(None); // Key too short
+                0x0 | 0x1 => {
+                    trace_node(&mut trace, NodeKind::Extension, &[]);
+
+                    // An extension always shares at least one nibble
+                    if node_path.is_empty() {
+                        return Err(ProofError::InvalidPath("Empty extension path".into()));
                     }
 
-                    let key_segment = &key_nibbles[key_index..key_index + path_len];
-                    if key_segment != node_path.as_slice() {
-                        return Ok(None); // Path doesn't match
+                    // Check that the path matches our key segment
+                    let path_len = node_path.len();
+                    let remaining_key = &key_nibbles[key_index..];
+                    if !remaining_key.starts_with(&node_path) {
+                        return exclusion(DivergenceReason::ExtensionPathMismatch);
                     }
 
                     // Update key index to skip the matched segment
                     key_index += path_len;
+                    trace_node(&mut trace, NodeKind::Extension, &node_path);
 
                     // Get the next node to look up
-                    let value_item = rlp.at(1)?;
-                    expected_hash = value_item.as_val()?;
+                    next_node = decode_node_ref(&rlp.at(1)?)?;
                 }
 
                 _ => return Err(ProofError::InvalidPath("Invalid node type".into())),
@@ -171,8 +460,6 @@ pub fn verify_proof(
             return Err(ProofError::InvalidProof("Invalid node format".into()));
         }
     }
-
-    Err(ProofError::InvalidProof("Proof too short".into()))
 }
 
 // Convert normal bytes to nibbles
@@ -185,28 +472,20 @@ pub fn encode_path(key: &[u8]) -> Vec<u8> {
     nibbles
 }
 
-// Decode compact encoding used in Ethereum's tries
-pub fn decode_compact(encoded: &[u8]) -> (u8, Vec<u8>) {
-    let mut result = Vec::new();
-    let mut node_type = encoded[0] >> 4;
-
-    // First nibble contains the type flag
-    if node_type == 0 || node_type == 2 {
-        // Even number of nibbles
-        for &byte in &encoded[1..] {
-            result.push(byte >> 4);
-            result.push(byte & 0x0f);
-        }
-    } else {
-        // Odd number of nibbles
+// Decode compact encoding used in Ethereum's tries into the node type flag and the path
+// nibbles. Flags 0/1 mark an extension and 2/3 a leaf, with an even/odd path length.
+pub fn decode_compact(encoded: &[u8]) -> Result<(u8, Vec<u8>), ProofError> {
+    validate_compact_path(encoded)?;
+    let node_type = encoded[0] >> 4;
+
+    let mut result = Vec::with_capacity(encoded.len() * 2);
+    // Odd paths start with the low nibble of the flag byte
+    if node_type & 1 == 1 {
         result.push(encoded[0] & 0x0f);
-        for &byte in &encoded[1..] {
-            result.push(byte >> 4);
-            result.push(byte & 0x0f);
-        }
     }
+    result.extend(encode_path(&encoded[1..]));
 
-    (node_type, result)
+    Ok((node_type, result))
 }
 
 // Account data structure matching Ethereum's state trie format
@@ -224,7 +503,7 @@ pub fn verify_account_proof(
     account_proof: &[Vec<u8>],
 ) -> Result<Option<AccountData>, ProofError> {
     // We need to hash the address with keccak256 to get the key for the state trie
-    let address_hash = Keccak256::digest(address).to_vec();
+    let address_hash = keccak256(address).as_bytes().to_vec();
 
     // Verify the proof to get the account RLP
     let account_rlp = match verify_proof(state_root, &address_hash, account_proof)? {
@@ -232,17 +511,19 @@ pub fn verify_account_proof(
         None => return Ok(None), // Account doesn't exist
     };
 
-    // Decode the account data
-    let rlp = Rlp::new(&account_rlp);
+    Ok(Some(decode_account(&account_rlp)?))
+}
+
+// Decode an account as stored in the state trie
+pub fn decode_account(account_rlp: &[u8]) -> Result<AccountData, ProofError> {
+    let rlp = Rlp::new(account_rlp);
 
-    let account = AccountData {
+    Ok(AccountData {
         nonce: rlp.at(0)?.as_val()?,
         balance: rlp.at(1)?.as_val()?,
         storage_root: rlp.at(2)?.as_val()?,
         code_hash: rlp.at(3)?.as_val()?,
-    };
-
-    Ok(Some(account))
+    })
 }
 
 pub fn verify_storage_proof(
@@ -251,7 +532,7 @@ pub fn verify_storage_proof(
     storage_proof: &[Vec<u8>],
 ) -> Result<Option<U256>, ProofError> {
     // Hash the key with keccak256
-    let key_hash = Keccak256::digest(key).to_vec();
+    let key_hash = keccak256(key).as_bytes().to_vec();
 
     // Verify the proof
     match verify_proof(storage_root, &key_hash, storage_proof)? {
@@ -264,30 +545,69 @@ pub fn verify_storage_proof(
     }
 }
 
+// Storage slot together with its proof nodes, as returned by eth_getProof
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageProof {
+    pub key: [u8; 32],
+    pub proof: Vec<Vec<u8>>,
+}
+
+// Proven storage values by slot
+pub type StorageValues = BTreeMap<[u8; 32], U256>;
+
+// Verify all storage proofs against the same storage root and return the proven value of
+// every slot. Slots that are proven to be absent from the trie hold zero.
+pub fn verify_storage_proofs(
+    storage_root: H256,
+    storage_proofs: &[StorageProof],
+) -> Result<StorageValues, ProofError> {
+    let mut values = BTreeMap::new();
+
+    for storage_proof in storage_proofs {
+        let value = verify_storage_proof(storage_root, &storage_proof.key, &storage_proof.proof)?
+            .unwrap_or_default();
+
+        if values.insert(storage_proof.key, value).is_some() {
+            return Err(ProofError::InvalidProof("Duplicate storage key".into()));
+        }
+    }
+
+    Ok(values)
+}
+
 // fn called in RISC Zero guest code:
+// The account proof is checked once, then every storage proof is verified against the
+// proven storage root. The returned map holds the value of every requested slot.
 pub fn verify_eth_proof(
     state_root: H256,
     address: [u8; 20],
-    key: Option<[u8; 32]>,
-    account_proof: Vec<Vec<u8>>,
-    storage_proof: Option<Vec<Vec<u8>>>,
-) -> Result<(Option<AccountData>, Option<U256>), ProofError> {
+    account_proof: &[Vec<u8>],
+    storage_proofs: &[StorageProof],
+) -> Result<(Option<AccountData>, StorageValues), ProofError> {
     // First verify the account proof
-    let account_data = verify_account_proof(state_root, &address, &account_proof)?;
+    let account_data = verify_account_proof(state_root, &address, account_proof)?;
 
-    // If no account exists or we don't need storage proof, return early
     let account = match account_data {
         Some(data) => data,
-        None => return Ok((None, None)),
+        None => {
+            // An account that does not exist has an empty storage, so every slot is zero.
+            // Nodes in its storage proofs can not belong to it.
+            let mut values = BTreeMap::new();
+            for storage_proof in storage_proofs {
+                if !storage_proof.proof.is_empty() {
+                    return Err(ProofError::InvalidProof(
+                        "Storage proof of a missing account".into(),
+                    ));
+                }
+                if values.insert(storage_proof.key, U256::zero()).is_some() {
+                    return Err(ProofError::InvalidProof("Duplicate storage key".into()));
+                }
+            }
+            return Ok((None, values));
+        }
     };
 
-    //NOTE: Commented out for now as for now we only want to check the account ballance really
-    // If we have a storage key, verify its proof
-    // let storage_value = if let (Some(k), Some(proof)) = (key, storage_proof) {
-    //     verify_storage_proof(account.storage_root, &k, &proof)?
-    // } else {
-    //     None
-    // };
+    let storage_values = verify_storage_proofs(account.storage_root, storage_proofs)?;
 
-    Ok((Some(account), None))
+    Ok((Some(account), storage_values))
 }
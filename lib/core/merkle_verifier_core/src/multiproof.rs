@@ -0,0 +1,154 @@
+extern crate alloc;
+use crate::keccak::keccak256;
+use crate::merkle_patricia::{
+    decode_account, walk_proof, AccountData, ProofError, ProofVerification, StorageValues,
+};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+use ethereum_types::{H256, U256};
+use rlp::Rlp;
+
+// Trie nodes of several proofs against the same root, indexed by their hash.
+//
+// Proofs for keys of the same trie share their upper nodes. Identical nodes are only
+// stored once and every distinct node is hashed exactly once when the cache is built,
+// looking up a node while walking a path does not hash anything.
+#[derive(Debug, Clone, Default)]
+pub struct NodeCache {
+    nodes: BTreeMap<H256, Vec<u8>>,
+}
+
+impl NodeCache {
+    pub fn from_proofs<'a>(proofs: impl IntoIterator<Item = &'a [Vec<u8>]>) -> Self {
+        // Deduplicate by content first, so shared nodes are not hashed again
+        let unique: BTreeSet<&Vec<u8>> = proofs.into_iter().flatten().collect();
+
+        let nodes = unique
+            .into_iter()
+            .map(|node| (keccak256(node), node.clone()))
+            .collect();
+
+        NodeCache { nodes }
+    }
+
+    // Number of distinct nodes
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    // Verify the key against the root, recording the hashes of all nodes on its path
+    fn walk(&self, root_hash: H256, key: &[u8], used: &mut BTreeSet<H256>) -> ProofVerification {
+        let mut node_by_hash = |hash: H256| {
+            let node = self.nodes.get(&hash).map(Vec::as_slice);
+            if node.is_some() {
+                used.insert(hash);
+            }
+            Ok(node)
+        };
+
+        match walk_proof(root_hash, key, &mut node_by_hash, None) {
+            Ok(verification) => verification,
+            Err(err) => ProofVerification::Invalid(err),
+        }
+    }
+
+    // Verify a single key against the root
+    pub fn verify(&self, root_hash: H256, key: &[u8]) -> ProofVerification {
+        self.walk(root_hash, key, &mut BTreeSet::new())
+    }
+
+    // Verify all keys against the root. Like a single proof, the cache must not contain
+    // nodes that are not on the path of any key.
+    pub fn verify_all(
+        &self,
+        root_hash: H256,
+        keys: &[&[u8]],
+    ) -> Result<Vec<Option<Vec<u8>>>, ProofError> {
+        let mut used = BTreeSet::new();
+
+        let values = keys
+            .iter()
+            .map(|key| self.walk(root_hash, key, &mut used).into_result())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if used.len() != self.nodes.len() {
+            return Err(ProofError::InvalidProof(
+                "Proof contains unused nodes".into(),
+            ));
+        }
+
+        Ok(values)
+    }
+}
+
+// Verify the proofs of several accounts against one state root. Returns the accounts in
+// the order of `accounts`, `None` for accounts that do not exist.
+pub fn verify_account_multiproof(
+    state_root: H256,
+    accounts: &[([u8; 20], Vec<Vec<u8>>)],
+) -> Result<Vec<Option<AccountData>>, ProofError> {
+    let cache = NodeCache::from_proofs(accounts.iter().map(|(_, proof)| proof.as_slice()));
+
+    let address_hashes: Vec<[u8; 32]> = accounts
+        .iter()
+        .map(|(address, _)| keccak256(address).into())
+        .collect();
+    let keys: Vec<&[u8]> = address_hashes.iter().map(|hash| hash.as_slice()).collect();
+
+    cache
+        .verify_all(state_root, &keys)?
+        .into_iter()
+        .map(|account_rlp| account_rlp.map(|rlp| decode_account(&rlp)).transpose())
+        .collect()
+}
+
+// Verify an account and storage slots with the nodes of the account proof and of all
+// storage proofs in one cache. The account is walked from the state root and the slots
+// from its storage root. Like `verify_all`, every node must be on one of these paths, so
+// the cache of a missing account holds no storage nodes. Slots that are absent from the
+// storage trie hold zero.
+pub fn verify_account_storage(
+    cache: &NodeCache,
+    state_root: H256,
+    address: &[u8; 20],
+    slots: &[[u8; 32]],
+) -> Result<(Option<AccountData>, StorageValues), ProofError> {
+    let mut used = BTreeSet::new();
+
+    let account = cache
+        .walk(state_root, keccak256(address).as_bytes(), &mut used)
+        .into_result()?
+        .map(|account_rlp| decode_account(&account_rlp))
+        .transpose()?;
+
+    let mut values = BTreeMap::new();
+    for slot in slots {
+        let value = match &account {
+            Some(account) => match cache
+                .walk(account.storage_root, keccak256(slot).as_bytes(), &mut used)
+                .into_result()?
+            {
+                Some(value_rlp) => Rlp::new(&value_rlp).as_val()?,
+                None => U256::zero(),
+            },
+            // An account that does not exist has an empty storage
+            None => U256::zero(),
+        };
+
+        if values.insert(*slot, value).is_some() {
+            return Err(ProofError::InvalidProof("Duplicate storage key".into()));
+        }
+    }
+
+    if used.len() != cache.len() {
+        return Err(ProofError::InvalidProof(
+            "Proof contains unused nodes".into(),
+        ));
+    }
+
+    Ok((account, values))
+}
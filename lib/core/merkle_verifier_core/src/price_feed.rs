@@ -0,0 +1,287 @@
+extern crate alloc;
+use crate::bundle::AccountProofBundle;
+use crate::framed::{FrameReader, FrameWriter};
+use crate::merkle_patricia::{ProofError, StorageValues};
+use crate::storage_layout::{
+    decode_address, decode_signed, extract_packed, mapping_slot, uint_key,
+};
+use crate::token::{address, TokenBalance};
+use alloc::vec;
+use alloc::vec::Vec;
+use ethereum_types::{H256, U256, U512};
+use serde::{Deserialize, Serialize};
+
+// Chainlink prices proven from the storage of the feed at a verified block.
+//
+// `latestRoundData` is answered by two contracts: the EACAggregatorProxy, whose address
+// is stable, forwards to the aggregator of its current phase. Both are proven at the same
+// state root: the proxy's `currentPhase` names the aggregator, the aggregator's
+// `s_hotVars` names its latest round and `s_transmissions` holds the answer of the round.
+//
+// Layouts (solc storage layout of the deployed sources):
+//   EACAggregatorProxy      slot 2:  currentPhase = { uint16 id, address aggregator }
+//   AccessControlled-       slot 43: s_hotVars = { bytes16 configDigest, uint40 epochAndRound,
+//   OffchainAggregator                             uint8 threshold, uint32 latestRoundId }
+//                           slot 44: s_transmissions = mapping(uint32 => { int192 answer,
+//                                                                          uint64 timestamp })
+//
+// Only the OCR1 aggregator has this layout, OCR2 aggregators keep their rounds elsewhere.
+// The verifier pins the code hashes of the aggregator deployments it checked to be OCR1,
+// an aggregator with any other code is rejected instead of being read with the wrong
+// layout.
+
+pub const PROXY_PHASE_SLOT: u64 = 2;
+pub const AGGREGATOR_HOT_VARS_SLOT: u64 = 43;
+pub const AGGREGATOR_TRANSMISSIONS_SLOT: u64 = 44;
+
+// What a feed prices its base asset in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Quote {
+    Eth,
+    Usd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeedProfile {
+    pub proxy: [u8; 20],
+    pub description: &'static str,
+    // Token the feed prices, as registered in `token::TOKEN_PROFILES`. Feeds of an asset
+    // that is not a token (ETH/USD) have none.
+    pub base: Option<[u8; 20]>,
+    pub quote: Quote,
+    pub decimals: u8,
+    // Longest time between two updates of the feed, a price older than this is stale
+    pub heartbeat: u64,
+}
+
+// Proxy and aggregator proofs of one feed, at the same block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceProof {
+    pub proxy: AccountProofBundle,
+    pub aggregator: AccountProofBundle,
+}
+
+// Latest round of a feed, as `latestRoundData` of the proxy returns it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvenPrice {
+    pub feed: [u8; 20],
+    pub aggregator: [u8; 20],
+    // Round id of the proxy: phase id in the bits above 64, round of the aggregator below
+    pub round_id: u128,
+    // Always positive, with `decimals` decimals
+    pub answer: U256,
+    pub decimals: u8,
+    pub updated_at: u64,
+}
+
+// Mainnet feeds of the collateral tokens. WETH needs none, it is worth its amount in ETH.
+pub const FEED_PROFILES: &[FeedProfile] = &[
+    FeedProfile {
+        proxy: address("0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"),
+        description: "ETH / USD",
+        base: None,
+        quote: Quote::Usd,
+        decimals: 8,
+        heartbeat: 3600,
+    },
+    FeedProfile {
+        proxy: address("0x986b5E1e1755e3C2440e960477f25201B0a8bbD4"),
+        description: "USDC / ETH",
+        base: Some(address("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48")),
+        quote: Quote::Eth,
+        decimals: 18,
+        heartbeat: 86400,
+    },
+    FeedProfile {
+        proxy: address("0xEe9F2375b4bdF6387aa8265dD4FB8F16512A1d46"),
+        description: "USDT / ETH",
+        base: Some(address("0xdAC17F958D2ee523a2206206994597C13D831ec7")),
+        quote: Quote::Eth,
+        decimals: 18,
+        heartbeat: 86400,
+    },
+    FeedProfile {
+        proxy: address("0x773616E4d11A78F511299002da57A0a94577F1f4"),
+        description: "DAI / ETH",
+        base: Some(address("0x6B175474E89094C44Da98b954EedeAC495271d0F")),
+        quote: Quote::Eth,
+        decimals: 18,
+        heartbeat: 86400,
+    },
+    FeedProfile {
+        // Prices WBTC as BTC
+        proxy: address("0xdeb288F737066589598e9214E782fa5A8eD689e8"),
+        description: "BTC / ETH",
+        base: Some(address("0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599")),
+        quote: Quote::Eth,
+        decimals: 18,
+        heartbeat: 86400,
+    },
+    FeedProfile {
+        proxy: address("0x86392dC19c0b719886221c78AB11eb8Cf5c52812"),
+        description: "STETH / ETH",
+        base: Some(address("0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84")),
+        quote: Quote::Eth,
+        decimals: 18,
+        heartbeat: 86400,
+    },
+];
+
+pub fn feed_profile(proxy: &[u8; 20]) -> Option<&'static FeedProfile> {
+    FEED_PROFILES.iter().find(|feed| &feed.proxy == proxy)
+}
+
+// Feed pricing `token` in ETH
+pub fn eth_feed_for(token: &[u8; 20]) -> Option<&'static FeedProfile> {
+    FEED_PROFILES
+        .iter()
+        .find(|feed| feed.base.as_ref() == Some(token) && feed.quote == Quote::Eth)
+}
+
+fn slot(number: u64) -> [u8; 32] {
+    H256::from_low_u64_be(number).into()
+}
+
+fn proven(values: &StorageValues, slot: &[u8; 32]) -> Result<U256, ProofError> {
+    values
+        .get(slot)
+        .copied()
+        .ok_or_else(|| ProofError::InvalidProof("Price feed slot is not proven".into()))
+}
+
+// Slot of the transmission of aggregator round `round_id`
+pub fn transmission_slot(round_id: u32) -> [u8; 32] {
+    mapping_slot(
+        &uint_key(U256::from(round_id)),
+        H256::from_low_u64_be(AGGREGATOR_TRANSMISSIONS_SLOT),
+    )
+    .into()
+}
+
+// Slots to request from the proxy
+pub fn proxy_slots() -> Vec<[u8; 32]> {
+    vec![slot(PROXY_PHASE_SLOT)]
+}
+
+// Slots to request from the aggregator. The latest round is only known from `s_hotVars`,
+// so the host reads that slot first.
+pub fn aggregator_slots(latest_round_id: u32) -> Vec<[u8; 32]> {
+    vec![
+        slot(AGGREGATOR_HOT_VARS_SLOT),
+        transmission_slot(latest_round_id),
+    ]
+}
+
+// Phase id and aggregator of the proxy's `currentPhase`
+pub fn decode_phase(value: U256) -> Result<(u16, [u8; 20]), ProofError> {
+    let id = extract_packed(value, 0, 2)?.low_u64() as u16;
+    let aggregator = decode_address(extract_packed(value, 2, 20)?)?;
+    Ok((id, aggregator.into()))
+}
+
+// Latest round of the aggregator from `s_hotVars`
+pub fn decode_latest_round(hot_vars: U256) -> Result<u32, ProofError> {
+    Ok(extract_packed(hot_vars, 22, 4)?.low_u32())
+}
+
+// Answer and timestamp of a transmission. The int192 answer is returned as its sign (true
+// if negative) and magnitude, every answer the aggregator can store decodes.
+pub fn decode_transmission(value: U256) -> Result<((bool, U256), u64), ProofError> {
+    let answer = decode_signed(extract_packed(value, 0, 24)?, 24)?;
+    let timestamp = extract_packed(value, 24, 8)?.low_u64();
+    Ok((answer, timestamp))
+}
+
+impl FeedProfile {
+    // The price is at most `heartbeat` seconds older than the block it was proven at
+    pub fn check_fresh(&self, price: &ProvenPrice, block_timestamp: u64) -> Result<(), ProofError> {
+        if price.updated_at > block_timestamp {
+            return Err(ProofError::InvalidValue(
+                "Price updated after the block".into(),
+            ));
+        }
+        if block_timestamp - price.updated_at > self.heartbeat {
+            return Err(ProofError::InvalidValue("Stale price".into()));
+        }
+        Ok(())
+    }
+}
+
+impl PriceProof {
+    pub fn write(&self, writer: &mut FrameWriter) {
+        writer.bundle(&self.proxy).bundle(&self.aggregator);
+    }
+
+    pub fn read(reader: &mut FrameReader) -> Result<Self, ProofError> {
+        Ok(PriceProof {
+            proxy: reader.bundle()?,
+            aggregator: reader.bundle()?,
+        })
+    }
+
+    // Verify both bundles against the state root of a block with `block_timestamp`, and
+    // decode the latest round of the feed. Stale prices are rejected, and so are
+    // aggregators whose code hash is not one of `ocr1_code_hashes`.
+    pub fn verify(
+        &self,
+        state_root: H256,
+        block_timestamp: u64,
+        ocr1_code_hashes: &[H256],
+    ) -> Result<ProvenPrice, ProofError> {
+        let feed = feed_profile(&self.proxy.address)
+            .ok_or_else(|| ProofError::InvalidProof("Unknown price feed".into()))?;
+
+        let (proxy, proxy_values) = self.proxy.verify(state_root)?;
+        let (aggregator, aggregator_values) = self.aggregator.verify(state_root)?;
+        let Some(aggregator) = aggregator.filter(|_| proxy.is_some()) else {
+            return Err(ProofError::InvalidProof(
+                "Price feed contract does not exist".into(),
+            ));
+        };
+        if !ocr1_code_hashes.contains(&aggregator.code_hash) {
+            return Err(ProofError::InvalidProof("Unknown aggregator layout".into()));
+        }
+
+        let (phase_id, current) = decode_phase(proven(&proxy_values, &slot(PROXY_PHASE_SLOT))?)?;
+        if current != self.aggregator.address {
+            return Err(ProofError::InvalidProof(
+                "Aggregator is not the current phase of the proxy".into(),
+            ));
+        }
+
+        let hot_vars = proven(&aggregator_values, &slot(AGGREGATOR_HOT_VARS_SLOT))?;
+        let round = decode_latest_round(hot_vars)?;
+        let transmission = proven(&aggregator_values, &transmission_slot(round))?;
+        let ((negative, answer), updated_at) = decode_transmission(transmission)?;
+        if negative || answer.is_zero() {
+            return Err(ProofError::InvalidValue("Non-positive price".into()));
+        }
+
+        let price = ProvenPrice {
+            feed: feed.proxy,
+            aggregator: current,
+            round_id: ((phase_id as u128) << 64) | round as u128,
+            answer,
+            decimals: feed.decimals,
+            updated_at,
+        };
+        feed.check_fresh(&price, block_timestamp)?;
+        Ok(price)
+    }
+}
+
+// Value of a proven token balance in wei, with the ETH price of the token
+pub fn eth_value(balance: &TokenBalance, price: &ProvenPrice) -> Result<U256, ProofError> {
+    let feed = feed_profile(&price.feed)
+        .ok_or_else(|| ProofError::InvalidValue("Unknown price feed".into()))?;
+    if feed.base != Some(balance.token) || feed.quote != Quote::Eth {
+        return Err(ProofError::InvalidValue(
+            "Price feed does not price the token in ETH".into(),
+        ));
+    }
+
+    // Normalized amounts have 18 decimals like wei, the answer has the feed's decimals
+    let scale = U256::exp10(feed.decimals as usize);
+    let value = balance.normalized.full_mul(price.answer) / U512::from(scale);
+    U256::try_from(value).map_err(|_| ProofError::InvalidValue("Collateral value overflows".into()))
+}
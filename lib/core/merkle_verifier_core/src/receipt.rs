@@ -0,0 +1,183 @@
+extern crate alloc;
+use crate::keccak::keccak256;
+use crate::merkle_patricia::{verify_proof, ProofError};
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use ethereum_types::{Bloom, H160, H256};
+use rlp::{Decodable, DecoderError, Rlp, RlpStream};
+use serde::{Deserialize, Serialize};
+
+// Highest EIP-2718 transaction type, larger first bytes start an RLP list (legacy receipt)
+const MAX_TX_TYPE: u8 = 0x7f;
+
+// Event emitted during a transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Log {
+    pub address: H160,
+    pub topics: Vec<H256>,
+    pub data: Vec<u8>,
+}
+
+impl Decodable for Log {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 3 {
+            return Err(DecoderError::RlpIncorrectListLen);
+        }
+
+        Ok(Log {
+            address: rlp.val_at(0)?,
+            topics: rlp.list_at(1)?,
+            data: rlp.val_at(2)?,
+        })
+    }
+}
+
+// Outcome of a transaction as committed in the receipt trie
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Receipt {
+    // EIP-2718 transaction type, 0 for legacy transactions
+    pub tx_type: u8,
+    // Post Byzantium status code. Older receipts hold the intermediate state root instead,
+    // they are reported as successful.
+    pub success: bool,
+    pub cumulative_gas_used: u64,
+    pub logs_bloom: Bloom,
+    pub logs: Vec<Log>,
+}
+
+impl Receipt {
+    // Logs matching the filter, in emission order
+    pub fn logs_matching<'a>(&'a self, filter: &'a LogFilter) -> impl Iterator<Item = &'a Log> {
+        self.logs.iter().filter(move |log| filter.matches(log))
+    }
+}
+
+// Selects logs by emitter and topics. `None` matches anything, `topics[i]` is compared
+// with the i-th topic of the log, like the topic filter of `eth_getLogs`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogFilter {
+    pub address: Option<H160>,
+    pub topics: Vec<Option<H256>>,
+}
+
+impl LogFilter {
+    // Logs of a single event (`topic0` is the event signature hash) emitted by `address`
+    pub fn event(address: H160, signature: &str) -> Self {
+        LogFilter {
+            address: Some(address),
+            topics: alloc::vec![Some(event_topic(signature))],
+        }
+    }
+
+    pub fn matches(&self, log: &Log) -> bool {
+        if self.address.is_some_and(|address| address != log.address) {
+            return false;
+        }
+
+        self.topics
+            .iter()
+            .enumerate()
+            .all(|(index, topic)| match topic {
+                Some(topic) => log.topics.get(index) == Some(topic),
+                None => true,
+            })
+    }
+}
+
+// Receipt of the transaction at `tx_index` together with its receipt trie proof
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptProof {
+    pub tx_index: u64,
+    pub proof: Vec<Vec<u8>>,
+}
+
+// topic0 of an event, e.g. `event_topic("Transfer(address,address,uint256)")`
+pub fn event_topic(signature: &str) -> H256 {
+    keccak256(signature.as_bytes())
+}
+
+// Key of a transaction in the receipt and transaction tries
+pub fn tx_index_key(tx_index: u64) -> Vec<u8> {
+    let mut stream = RlpStream::new();
+    stream.append(&tx_index);
+    stream.out().to_vec()
+}
+
+// Decode a receipt as stored in the receipt trie: the RLP list for legacy receipts,
+// `tx_type || rlp(list)` for typed receipts
+pub fn decode_receipt(encoded: &[u8]) -> Result<Receipt, ProofError> {
+    let (tx_type, payload) = match encoded.first() {
+        None => return Err(ProofError::RlpDecoding("Empty receipt".into())),
+        Some(&tx_type) if tx_type <= MAX_TX_TYPE => (tx_type, &encoded[1..]),
+        Some(_) => (0, encoded),
+    };
+    // A legacy receipt is never prefixed with its type
+    if tx_type == 0 && payload.len() != encoded.len() {
+        return Err(ProofError::RlpDecoding("Unsupported receipt type".into()));
+    }
+
+    let rlp = Rlp::new(payload);
+    if !rlp.is_list() || rlp.payload_info()?.total() != payload.len() {
+        return Err(ProofError::RlpDecoding("Invalid receipt encoding".into()));
+    }
+    if rlp.item_count()? != 4 {
+        return Err(ProofError::RlpDecoding(
+            "Invalid receipt field count".into(),
+        ));
+    }
+
+    // Status code (0 or 1) after Byzantium, 32 byte state root before
+    let status = rlp.at(0)?.data()?;
+    let success = match status {
+        [] => false,
+        [1] => true,
+        _ if status.len() == 32 => true,
+        _ => return Err(ProofError::RlpDecoding("Invalid receipt status".into())),
+    };
+
+    Ok(Receipt {
+        tx_type,
+        success,
+        cumulative_gas_used: rlp.val_at(1)?,
+        logs_bloom: rlp.val_at(2)?,
+        logs: rlp.list_at(3)?,
+    })
+}
+
+// Verify the receipt of the transaction at `tx_index` against a block's receipts root.
+// Returns `None` if the block has no transaction at that index.
+pub fn verify_receipt_proof(
+    receipts_root: H256,
+    tx_index: u64,
+    proof: &[Vec<u8>],
+) -> Result<Option<Receipt>, ProofError> {
+    match verify_proof(receipts_root, &tx_index_key(tx_index), proof)? {
+        Some(encoded) => Ok(Some(decode_receipt(&encoded)?)),
+        None => Ok(None),
+    }
+}
+
+// Verify every receipt proof and count the logs matching the filter. Every transaction may
+// only be proven once, so a log can not be counted twice.
+pub fn count_proven_logs(
+    receipts_root: H256,
+    receipt_proofs: &[ReceiptProof],
+    filter: &LogFilter,
+) -> Result<usize, ProofError> {
+    let mut seen = BTreeSet::new();
+    let mut count = 0;
+
+    for receipt_proof in receipt_proofs {
+        if !seen.insert(receipt_proof.tx_index) {
+            return Err(ProofError::InvalidProof("Duplicate receipt proof".into()));
+        }
+
+        let receipt =
+            verify_receipt_proof(receipts_root, receipt_proof.tx_index, &receipt_proof.proof)?
+                .ok_or(ProofError::InvalidProof("Receipt does not exist".into()))?;
+
+        count += receipt.logs_matching(filter).count();
+    }
+
+    Ok(count)
+}
@@ -0,0 +1,264 @@
+extern crate alloc;
+use crate::bundle::AccountProofBundle;
+use crate::framed::{FrameReader, FrameWriter};
+use crate::keccak::keccak256;
+use crate::merkle_patricia::{ProofError, StorageValues};
+use crate::storage_layout::mapping_slot;
+use crate::token::{address, token_profile, ShareRate};
+use alloc::vec;
+use alloc::vec::Vec;
+use ethereum_types::{H256, U256};
+use serde::{Deserialize, Serialize};
+
+// ETH value of liquid staking tokens, from the rate of their protocol at the same block.
+//
+// stETH balances are shares of Lido's pooled ether, wstETH wraps the shares one to one.
+// Both are worth `shares * totalPooledEther / totalShares`, read from Lido's unstructured
+// storage (positions are keccak256 of their names, see Lido V2 `Lido.sol` and `StETH.sol`):
+//   totalPooledEther = bufferedEther + beaconBalance
+//                      + (depositedValidators - beaconValidators) * 32 ether
+//
+// rETH is worth `amount * totalEthBalance / rethSupply`, both kept by RocketNetworkBalances
+// in the `uintStorage` mapping (slot 2) of RocketStorage.
+
+pub const LIDO: [u8; 20] = address("0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84");
+pub const WSTETH: [u8; 20] = address("0x7f39C581F595B53c5cb19bD0b3f8dA6c935E2Ca0");
+pub const RETH: [u8; 20] = address("0xae78736Cd615f374D3085123A210448E74Fc6393");
+pub const ROCKET_STORAGE: [u8; 20] = address("0x1d8f8f00cfa6758d7bE78336684788Fb0ee0Fa46");
+
+const ROCKET_UINT_STORAGE_SLOT: u64 = 2;
+// Ether deposited per validator
+const DEPOSIT_SIZE_ETH: u64 = 32;
+
+// Liquid staking tokens with an ETH rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StakingToken {
+    StEth,
+    WstEth,
+    REth,
+}
+
+impl StakingToken {
+    pub fn from_address(token: &[u8; 20]) -> Option<Self> {
+        match *token {
+            LIDO => Some(StakingToken::StEth),
+            WSTETH => Some(StakingToken::WstEth),
+            RETH => Some(StakingToken::REth),
+            _ => None,
+        }
+    }
+
+    pub fn address(self) -> [u8; 20] {
+        match self {
+            StakingToken::StEth => LIDO,
+            StakingToken::WstEth => WSTETH,
+            StakingToken::REth => RETH,
+        }
+    }
+
+    // Contract whose storage holds the rate
+    pub fn rate_contract(self) -> [u8; 20] {
+        match self {
+            StakingToken::StEth | StakingToken::WstEth => LIDO,
+            StakingToken::REth => ROCKET_STORAGE,
+        }
+    }
+
+    // Slots of the rate contract the host has to request
+    pub fn rate_slots(self) -> Vec<[u8; 32]> {
+        match self {
+            StakingToken::StEth | StakingToken::WstEth => LIDO_RATE_POSITIONS
+                .iter()
+                .map(|name| position(name))
+                .collect(),
+            StakingToken::REth => ROCKET_RATE_KEYS
+                .iter()
+                .map(|key| rocket_uint(key))
+                .collect(),
+        }
+    }
+
+    // Slots of the token contract and of the rate contract the host has to request for
+    // `holder`. stETH is its own rate contract, all of its slots are in the token proof.
+    pub fn required_slots(self, holder: &[u8; 20]) -> (Vec<[u8; 32]>, Vec<[u8; 32]>) {
+        let balance_slots = token_profile(&self.address())
+            .expect("Liquid staking tokens are registered")
+            .required_slots(holder);
+        match self {
+            StakingToken::StEth => ([balance_slots, self.rate_slots()].concat(), vec![]),
+            StakingToken::WstEth | StakingToken::REth => (balance_slots, self.rate_slots()),
+        }
+    }
+
+    // Rate from the proven storage values of `rate_contract`
+    pub fn rate_from_values(self, values: &StorageValues) -> Result<ShareRate, ProofError> {
+        match self {
+            StakingToken::StEth | StakingToken::WstEth => lido_rate(values),
+            StakingToken::REth => rocket_pool_rate(values),
+        }
+    }
+}
+
+// Lido V2 unstructured storage positions read by `getTotalPooledEther` and `getTotalShares`
+const LIDO_RATE_POSITIONS: [&str; 5] = [
+    "lido.Lido.bufferedEther",
+    "lido.Lido.beaconBalance",
+    "lido.Lido.depositedValidators",
+    "lido.Lido.beaconValidators",
+    "lido.StETH.totalShares",
+];
+
+// RocketNetworkBalances keys of the total ETH balance and the rETH supply
+const ROCKET_RATE_KEYS: [&str; 2] = ["network.balance.total", "network.balance.reth.supply"];
+
+fn position(name: &str) -> [u8; 32] {
+    keccak256(name.as_bytes()).into()
+}
+
+// Slot of `uintStorage[keccak256(key)]` in RocketStorage
+fn rocket_uint(key: &str) -> [u8; 32] {
+    let key: [u8; 32] = keccak256(key.as_bytes()).into();
+    mapping_slot(&key, H256::from_low_u64_be(ROCKET_UINT_STORAGE_SLOT)).into()
+}
+
+fn invalid_proof(msg: &str) -> ProofError {
+    ProofError::InvalidProof(msg.into())
+}
+
+fn proven(values: &StorageValues, slot: &[u8; 32]) -> Result<U256, ProofError> {
+    values
+        .get(slot)
+        .copied()
+        .ok_or_else(|| invalid_proof("Staking rate slot is not proven"))
+}
+
+fn overflow() -> ProofError {
+    ProofError::InvalidValue("Total pooled ether overflows".into())
+}
+
+// Pooled ether and total shares of Lido
+pub fn lido_rate(values: &StorageValues) -> Result<ShareRate, ProofError> {
+    let [buffered, beacon_balance, deposited, beacon_validators, shares] =
+        LIDO_RATE_POSITIONS.map(|name| proven(values, &position(name)));
+
+    let transient_validators = deposited?
+        .checked_sub(beacon_validators?)
+        .ok_or_else(|| ProofError::InvalidValue("More validators than deposited".into()))?;
+    let transient = transient_validators
+        .checked_mul(U256::from(DEPOSIT_SIZE_ETH) * U256::exp10(18))
+        .ok_or_else(overflow)?;
+    let pooled = buffered?
+        .checked_add(beacon_balance?)
+        .and_then(|pooled| pooled.checked_add(transient))
+        .ok_or_else(overflow)?;
+
+    Ok(ShareRate {
+        pooled,
+        shares: shares?,
+    })
+}
+
+// Total ETH balance and rETH supply of Rocket Pool
+pub fn rocket_pool_rate(values: &StorageValues) -> Result<ShareRate, ProofError> {
+    let [total, supply] = ROCKET_RATE_KEYS.map(|key| proven(values, &rocket_uint(key)));
+    Ok(ShareRate {
+        pooled: total?,
+        shares: supply?,
+    })
+}
+
+// Token balance of a holder with the rate of its protocol, both at the same block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakingProof {
+    pub token: AccountProofBundle,
+    // Proof of the rate slots. For stETH the token is the rate contract, its bundle holds
+    // the balance and the rate slots and this one is `None`.
+    pub rate: Option<AccountProofBundle>,
+}
+
+// Proven holding of a liquid staking token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakedBalance {
+    pub token: [u8; 20],
+    pub holder: [u8; 20],
+    // Balance in token units (for stETH already in ETH)
+    pub amount: U256,
+    // ETH equivalent in wei, rounded down like the protocol does
+    pub eth: U256,
+}
+
+impl StakingProof {
+    // The rate bundle is preceded by a word that tells whether it is present
+    pub fn write(&self, writer: &mut FrameWriter) {
+        writer.bundle(&self.token);
+        match &self.rate {
+            Some(rate) => writer.u32(1).bundle(rate),
+            None => writer.u32(0),
+        };
+    }
+
+    pub fn read(reader: &mut FrameReader) -> Result<Self, ProofError> {
+        let token = reader.bundle()?;
+        let rate = match reader.u32()? {
+            0 => None,
+            1 => Some(reader.bundle()?),
+            _ => return Err(invalid_proof("Invalid staking rate flag")),
+        };
+        Ok(StakingProof { token, rate })
+    }
+
+    pub fn verify(&self, state_root: H256, holder: &[u8; 20]) -> Result<StakedBalance, ProofError> {
+        let kind = StakingToken::from_address(&self.token.address)
+            .ok_or_else(|| invalid_proof("Not a liquid staking token"))?;
+        let profile = token_profile(&kind.address())
+            .ok_or_else(|| invalid_proof("Liquid staking token is not registered"))?;
+
+        let (token, token_values) = self.token.verify(state_root)?;
+        if token.is_none() {
+            return Err(invalid_proof("Token contract does not exist"));
+        }
+
+        let rate_values = match (kind, &self.rate) {
+            (StakingToken::StEth, None) => token_values.clone(),
+            (StakingToken::WstEth | StakingToken::REth, Some(rate)) => {
+                if rate.address != kind.rate_contract() {
+                    return Err(invalid_proof("Staking rate proven from the wrong contract"));
+                }
+                let (account, values) = rate.verify(state_root)?;
+                if account.is_none() {
+                    return Err(invalid_proof("Staking rate contract does not exist"));
+                }
+                values
+            }
+            _ => return Err(invalid_proof("Staking rate proof does not match the token")),
+        };
+        let rate = kind.rate_from_values(&rate_values)?;
+
+        let balance = profile.balance_from_values(holder, &token_values, Some(&rate))?;
+        let eth = match kind {
+            // The balance of stETH is its amount of pooled ether
+            StakingToken::StEth => balance.amount,
+            StakingToken::WstEth => rate.amount(balance.amount)?,
+            // RocketTokenRETH.getEthValue values rETH one to one without a supply
+            StakingToken::REth if rate.shares.is_zero() => balance.amount,
+            StakingToken::REth => rate.amount(balance.amount)?,
+        };
+
+        Ok(StakedBalance {
+            token: kind.address(),
+            holder: *holder,
+            amount: balance.amount,
+            eth,
+        })
+    }
+}
+
+// Sum of the ETH equivalents in wei, to add to the ETH balance of the owned accounts. The
+// score input keeps balances as `u128`, larger sums are rejected.
+pub fn total_eth_equivalent(balances: &[StakedBalance]) -> Result<u128, ProofError> {
+    let overflow = || ProofError::InvalidValue("ETH equivalent overflows".into());
+    balances.iter().try_fold(0u128, |total, balance| {
+        let eth = u128::try_from(balance.eth).map_err(|_| overflow())?;
+        total.checked_add(eth).ok_or_else(overflow)
+    })
+}
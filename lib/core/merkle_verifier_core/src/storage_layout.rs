@@ -0,0 +1,226 @@
+// Helpers to locate and decode Solidity state variables in proven storage slots.
+//
+// This is the `no_std` counterpart of `fetch_merkle::calculate_mapping_slot`: the host
+// uses the same slot derivation to request the storage proofs, the guest to check that a
+// proven slot belongs to the variable it is interested in and to decode its value.
+// Offsets of packed members are counted in bytes from the least significant (right) end
+// of the slot, like Solidity lays them out.
+
+extern crate alloc;
+use crate::keccak::keccak256;
+use crate::merkle_patricia::ProofError;
+use alloc::string::String;
+use alloc::vec::Vec;
+use ethereum_types::{H160, H256, U256};
+
+fn invalid_value(msg: &str) -> ProofError {
+    ProofError::InvalidValue(msg.into())
+}
+
+// Mapping key of an address, left padded to 32 bytes
+pub fn address_key(address: &[u8; 20]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    key[12..].copy_from_slice(address);
+    key
+}
+
+// Mapping key of a value type (uint, int, bool, bytes32, ...) already padded to 32 bytes
+pub fn uint_key(value: U256) -> [u8; 32] {
+    value.into()
+}
+
+// Slot of `mapping[key]` for a mapping at `slot`: keccak256(key . slot)
+pub fn mapping_slot(key: &[u8; 32], slot: H256) -> H256 {
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(key);
+    data[32..].copy_from_slice(slot.as_bytes());
+    keccak256(&data)
+}
+
+// Slot of `mapping[key]` for `string` and `bytes` keys, which are hashed unpadded
+pub fn mapping_slot_for_bytes_key(key: &[u8], slot: H256) -> H256 {
+    let mut data = Vec::with_capacity(key.len() + 32);
+    data.extend_from_slice(key);
+    data.extend_from_slice(slot.as_bytes());
+    keccak256(&data)
+}
+
+// Slot of `mapping[keys[0]][keys[1]]...` for nested mappings
+pub fn nested_mapping_slot(keys: &[[u8; 32]], slot: H256) -> H256 {
+    keys.iter().fold(slot, |slot, key| mapping_slot(key, slot))
+}
+
+// Slot `offset` slots after `slot`, e.g. a struct member. Wraps around like the EVM does.
+pub fn slot_offset(slot: H256, offset: U256) -> H256 {
+    let (slot, _) = U256::from_big_endian(slot.as_bytes()).overflowing_add(offset);
+    H256::from(uint_key(slot))
+}
+
+// Length of a dynamic array stored at `slot`, the slot value holds the length
+pub fn array_length(value: U256) -> Result<u64, ProofError> {
+    if value > U256::from(u64::MAX) {
+        return Err(invalid_value("Array length out of range"));
+    }
+    Ok(value.as_u64())
+}
+
+// Slot of element `index` of a dynamic array at `slot` whose elements take up
+// `slots_per_element` full slots. The elements start at keccak256(slot).
+pub fn array_element_slot(array_slot: H256, index: u64, slots_per_element: u64) -> H256 {
+    let data_slot = keccak256(array_slot.as_bytes());
+    slot_offset(data_slot, U256::from(index) * U256::from(slots_per_element))
+}
+
+// Slot and byte offset of element `index` of a dynamic array of elements narrower than a
+// slot (e.g. `uint64[]`), which are packed `32 / width` per slot
+pub fn packed_array_element(
+    array_slot: H256,
+    index: u64,
+    width: usize,
+) -> Result<(H256, usize), ProofError> {
+    if width == 0 || width > 32 {
+        return Err(invalid_value("Invalid element width"));
+    }
+
+    let per_slot = (32 / width) as u64;
+    let slot = array_element_slot(array_slot, index / per_slot, 1);
+    Ok((slot, (index % per_slot) as usize * width))
+}
+
+// Packed member of `width` bytes at byte `offset` of a slot value
+pub fn extract_packed(value: U256, offset: usize, width: usize) -> Result<U256, ProofError> {
+    if width == 0 || offset > 32 || width > 32 - offset {
+        return Err(invalid_value("Packed member exceeds the slot"));
+    }
+
+    let shifted = value >> (offset * 8);
+    if width == 32 {
+        return Ok(shifted);
+    }
+    Ok(shifted & ((U256::one() << (width * 8)) - 1))
+}
+
+// `uintN` of `width` bytes, all bits above it have to be zero
+pub fn decode_uint(value: U256, width: usize) -> Result<U256, ProofError> {
+    if extract_packed(value, 0, width)? != value {
+        return Err(invalid_value("Value exceeds the integer width"));
+    }
+    Ok(value)
+}
+
+pub fn decode_bool(value: U256) -> Result<bool, ProofError> {
+    if value.is_zero() {
+        Ok(false)
+    } else if value == U256::one() {
+        Ok(true)
+    } else {
+        Err(invalid_value("Invalid bool value"))
+    }
+}
+
+pub fn decode_address(value: U256) -> Result<H160, ProofError> {
+    let value = decode_uint(value, 20)?;
+    Ok(H160::from_slice(&uint_key(value)[12..]))
+}
+
+// `intN` of `width` bytes in two's complement as its sign (true if negative) and
+// magnitude. Solidity cleans packed members, so the slot value holds just the `width`
+// bytes without sign extension.
+pub fn decode_signed(value: U256, width: usize) -> Result<(bool, U256), ProofError> {
+    let value = decode_uint(value, width)?;
+    let bits = width * 8;
+
+    let negative = value.bit(bits - 1);
+    // Two's complement within `bits`, 2^256 - value is computed as !value + 1
+    let magnitude = match (negative, bits) {
+        (false, _) => value,
+        (true, 256) => (!value).overflowing_add(U256::one()).0,
+        (true, _) => (U256::one() << bits) - value,
+    };
+    Ok((negative, magnitude))
+}
+
+// `intN` of `width` bytes, see `decode_signed`. Values that do not fit an `i128` are
+// rejected.
+pub fn decode_int(value: U256, width: usize) -> Result<i128, ProofError> {
+    let (negative, magnitude) = decode_signed(value, width)?;
+
+    if magnitude > U256::from(i128::MAX as u128) + U256::from(negative as u8) {
+        return Err(invalid_value("Integer does not fit in an i128"));
+    }
+
+    let magnitude = magnitude.as_u128();
+    Ok(if negative {
+        0i128.wrapping_sub_unsigned(magnitude)
+    } else {
+        magnitude as i128
+    })
+}
+
+// Content of a `bytes` or `string` slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BytesValue {
+    // Up to 31 bytes are stored in the slot itself
+    Short(Vec<u8>),
+    // Longer values are stored in consecutive slots starting at `data_slot`
+    Long { length: u64, data_slot: H256 },
+}
+
+// Decode the slot of a `bytes` or `string` variable stored at `slot`. Short values are
+// stored left aligned with `2 * length` in the lowest byte, long values store
+// `2 * length + 1` and the data at keccak256(slot).
+pub fn decode_bytes_slot(slot: H256, value: U256) -> Result<BytesValue, ProofError> {
+    if value.bit(0) {
+        let length = array_length(value >> 1)?;
+        if length < 32 {
+            return Err(invalid_value("Long bytes encoding of a short value"));
+        }
+        return Ok(BytesValue::Long {
+            length,
+            data_slot: keccak256(slot.as_bytes()),
+        });
+    }
+
+    let bytes = uint_key(value);
+    let length = (bytes[31] / 2) as usize;
+    if length > 31 || bytes[length..31].iter().any(|&b| b != 0) {
+        return Err(invalid_value("Invalid short bytes encoding"));
+    }
+    Ok(BytesValue::Short(bytes[..length].to_vec()))
+}
+
+// Longest `bytes` or `string` value whose data slots are derived, 128 slots. The length
+// comes from the proven slot, so without a bound a contract could make the prover
+// allocate and request up to 2^59 slots.
+pub const MAX_LONG_BYTES_LENGTH: u64 = 4096;
+
+// Slots holding the data of a long `bytes` or `string` value
+pub fn long_bytes_slots(data_slot: H256, length: u64) -> Result<Vec<H256>, ProofError> {
+    if length > MAX_LONG_BYTES_LENGTH {
+        return Err(invalid_value("Bytes value too long"));
+    }
+    Ok((0..length.div_ceil(32))
+        .map(|index| slot_offset(data_slot, U256::from(index)))
+        .collect())
+}
+
+// Concatenate the proven data slots of a long `bytes` or `string` value
+pub fn assemble_long_bytes(values: &[U256], length: u64) -> Result<Vec<u8>, ProofError> {
+    if values.len() as u64 != length.div_ceil(32) {
+        return Err(invalid_value("Wrong number of data slots"));
+    }
+
+    let mut bytes: Vec<u8> = values.iter().flat_map(|value| uint_key(*value)).collect();
+    bytes.truncate(length as usize);
+    Ok(bytes)
+}
+
+// `string` stored in a single slot
+pub fn decode_short_string(slot: H256, value: U256) -> Result<String, ProofError> {
+    match decode_bytes_slot(slot, value)? {
+        BytesValue::Short(bytes) => {
+            String::from_utf8(bytes).map_err(|_| invalid_value("Invalid UTF-8 string"))
+        }
+        BytesValue::Long { .. } => Err(invalid_value("String is stored in multiple slots")),
+    }
+}
@@ -0,0 +1,366 @@
+// In-memory Merkle-Patricia tries to build roots and proofs offline, for tests and
+// fixtures that would otherwise need an Anvil or archive node. Proofs are emitted like
+// `eth_getProof` does: the nodes on the path to the key, root first, without the nodes
+// that are embedded in their parent because their encoding is shorter than 32 bytes.
+//
+// Only enabled with the `test-utils` feature. Nothing here is meant to be efficient:
+// every root and proof is computed from scratch.
+
+extern crate alloc;
+use crate::block_header::BlockHeader;
+use crate::bundle::AccountProofBundle;
+use crate::keccak::keccak256;
+use crate::merkle_patricia::{encode_path, StorageProof, EMPTY_TRIE_ROOT};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use ethereum_types::{Bloom, H160, H256, H64, U256};
+use rlp::RlpStream;
+
+// Hex prefix encoding of a leaf or extension path
+fn compact(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 2 } else { 0 };
+    let mut encoded = Vec::with_capacity(nibbles.len() / 2 + 1);
+
+    let rest = if nibbles.len() % 2 == 1 {
+        encoded.push(((flag + 1) << 4) | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        encoded.push(flag << 4);
+        nibbles
+    };
+    for pair in rest.chunks(2) {
+        encoded.push((pair[0] << 4) | pair[1]);
+    }
+    encoded
+}
+
+// How a parent refers to a child: embedded if its encoding is shorter than a hash
+fn append_child(stream: &mut RlpStream, node: &[u8]) {
+    if node.len() < 32 {
+        stream.append_raw(node, 1);
+    } else {
+        stream.append(&keccak256(node));
+    }
+}
+
+fn common_prefix(entries: &[(Vec<u8>, &[u8])], depth: usize) -> usize {
+    let first = &entries[0].0[depth..];
+    entries[1..].iter().fold(first.len(), |length, (path, _)| {
+        first[..length]
+            .iter()
+            .zip(&path[depth..])
+            .take_while(|(a, b)| a == b)
+            .count()
+    })
+}
+
+// Encode the node holding `entries`, sorted by path, whose paths agree up to `depth`.
+// When `key` is set, the encodings of the nodes on its path are appended to `proof`.
+fn encode_node(
+    entries: &[(Vec<u8>, &[u8])],
+    depth: usize,
+    key: Option<&[u8]>,
+    proof: &mut Vec<Vec<u8>>,
+) -> Vec<u8> {
+    // Children are encoded before their parent, so the proof of the subtree is collected
+    // separately and placed after the node itself
+    let mut child_proof = Vec::new();
+
+    let node = if entries.len() == 1 {
+        let (path, value) = &entries[0];
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&compact(&path[depth..], true));
+        stream.append(value);
+        stream.out().to_vec()
+    } else {
+        let prefix = common_prefix(entries, depth);
+        if prefix > 0 {
+            let follow = key.filter(|key| {
+                key.get(depth..depth + prefix) == Some(&entries[0].0[depth..depth + prefix])
+            });
+            let child = encode_node(entries, depth + prefix, follow, &mut child_proof);
+
+            let mut stream = RlpStream::new_list(2);
+            stream.append(&compact(&entries[0].0[depth..depth + prefix], false));
+            append_child(&mut stream, &child);
+            stream.out().to_vec()
+        } else {
+            let mut stream = RlpStream::new_list(17);
+            // Only one key can end at this branch, it sorts before all others
+            let (value, children) = match entries[0].0.len() == depth {
+                true => (Some(entries[0].1), &entries[1..]),
+                false => (None, entries),
+            };
+
+            for nibble in 0..16u8 {
+                let start = children.partition_point(|(path, _)| path[depth] < nibble);
+                let end = children.partition_point(|(path, _)| path[depth] <= nibble);
+                if start == end {
+                    stream.append_empty_data();
+                    continue;
+                }
+
+                let follow = key.filter(|key| key.get(depth) == Some(&nibble));
+                let child = encode_node(&children[start..end], depth + 1, follow, &mut child_proof);
+                append_child(&mut stream, &child);
+            }
+            match value {
+                Some(value) => stream.append(&value),
+                None => stream.append_empty_data(),
+            };
+            stream.out().to_vec()
+        }
+    };
+
+    if key.is_some() && (depth == 0 || node.len() >= 32) {
+        proof.push(node.clone());
+    }
+    proof.append(&mut child_proof);
+    node
+}
+
+// Trie of raw keys and values, like the transaction and receipt tries
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryTrie {
+    entries: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl MemoryTrie {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Empty values are not stored in Ethereum tries, inserting one removes the key
+    pub fn insert(&mut self, key: &[u8], value: Vec<u8>) {
+        if value.is_empty() {
+            self.entries.remove(key);
+        } else {
+            self.entries.insert(key.to_vec(), value);
+        }
+    }
+
+    pub fn remove(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+        self.entries.remove(key)
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.entries.get(key).map(|value| value.as_slice())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn encode(&self, key: Option<&[u8]>, proof: &mut Vec<Vec<u8>>) -> Option<Vec<u8>> {
+        if self.entries.is_empty() {
+            return None;
+        }
+
+        let entries: Vec<(Vec<u8>, &[u8])> = self
+            .entries
+            .iter()
+            .map(|(key, value)| (encode_path(key), value.as_slice()))
+            .collect();
+        let key = key.map(encode_path);
+        Some(encode_node(&entries, 0, key.as_deref(), proof))
+    }
+
+    pub fn root(&self) -> H256 {
+        match self.encode(None, &mut Vec::new()) {
+            Some(root) => keccak256(&root),
+            None => EMPTY_TRIE_ROOT,
+        }
+    }
+
+    // Proof of inclusion of `key`, or of its exclusion if the trie does not hold it
+    pub fn proof(&self, key: &[u8]) -> Vec<Vec<u8>> {
+        let mut proof = Vec::new();
+        self.encode(Some(key), &mut proof);
+        proof
+    }
+}
+
+// Trie keyed by the hash of the key, like the state and storage tries
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SecureTrie {
+    trie: MemoryTrie,
+}
+
+impl SecureTrie {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, key: &[u8], value: Vec<u8>) {
+        self.trie.insert(keccak256(key).as_bytes(), value);
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.trie.get(keccak256(key).as_bytes())
+    }
+
+    pub fn root(&self) -> H256 {
+        self.trie.root()
+    }
+
+    pub fn proof(&self, key: &[u8]) -> Vec<Vec<u8>> {
+        self.trie.proof(keccak256(key).as_bytes())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct TestAccount {
+    nonce: U256,
+    balance: U256,
+    code_hash: H256,
+    storage: SecureTrie,
+}
+
+// Runtime code of test contracts whose code does not matter: the start of every solc
+// contract, `mstore(0x40, 0x80)`
+pub const CONTRACT_CODE: &[u8] = &[0x60, 0x80, 0x60, 0x40, 0x52];
+
+// Builds a whole state trie with accounts and their storage, and the `eth_getProof`
+// proofs of any account and slot in it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateBuilder {
+    accounts: BTreeMap<[u8; 20], TestAccount>,
+}
+
+impl StateBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entry(&mut self, address: [u8; 20]) -> &mut TestAccount {
+        self.accounts.entry(address).or_insert_with(|| TestAccount {
+            code_hash: keccak256(&[]),
+            ..TestAccount::default()
+        })
+    }
+
+    // Create the account if needed and set its nonce and balance
+    pub fn account(&mut self, address: [u8; 20], nonce: u64, balance: U256) -> &mut Self {
+        let account = self.entry(address);
+        account.nonce = U256::from(nonce);
+        account.balance = balance;
+        self
+    }
+
+    pub fn code(&mut self, address: [u8; 20], code: &[u8]) -> &mut Self {
+        self.entry(address).code_hash = keccak256(code);
+        self
+    }
+
+    // Deployed contract without balance, with `CONTRACT_CODE`
+    pub fn contract(&mut self, address: [u8; 20]) -> &mut Self {
+        self.account(address, 1, U256::zero())
+            .code(address, CONTRACT_CODE)
+    }
+
+    // Zero values are removed from the storage trie, like the EVM does
+    pub fn storage(&mut self, address: [u8; 20], slot: [u8; 32], value: U256) -> &mut Self {
+        let value = match value.is_zero() {
+            true => Vec::new(),
+            false => rlp::encode(&value).to_vec(),
+        };
+        self.entry(address).storage.insert(&slot, value);
+        self
+    }
+
+    pub fn storage_root(&self, address: [u8; 20]) -> H256 {
+        self.accounts
+            .get(&address)
+            .map_or(EMPTY_TRIE_ROOT, |account| account.storage.root())
+    }
+
+    fn state_trie(&self) -> SecureTrie {
+        let mut trie = SecureTrie::new();
+        for (address, account) in &self.accounts {
+            let mut stream = RlpStream::new_list(4);
+            stream.append(&account.nonce);
+            stream.append(&account.balance);
+            stream.append(&account.storage.root());
+            stream.append(&account.code_hash);
+            trie.insert(address, stream.out().to_vec());
+        }
+        trie
+    }
+
+    pub fn state_root(&self) -> H256 {
+        self.state_trie().root()
+    }
+
+    pub fn account_proof(&self, address: [u8; 20]) -> Vec<Vec<u8>> {
+        self.state_trie().proof(&address)
+    }
+
+    // The proof of a slot of a missing account is empty, like the proof of any slot of
+    // an empty storage trie
+    pub fn storage_proof(&self, address: [u8; 20], slot: [u8; 32]) -> StorageProof {
+        StorageProof {
+            key: slot,
+            proof: self
+                .accounts
+                .get(&address)
+                .map_or_else(Vec::new, |account| account.storage.proof(&slot)),
+        }
+    }
+
+    // Everything `eth_getProof(address, slots)` returns, in the input schema of the guests
+    pub fn bundle(&self, address: [u8; 20], slots: &[[u8; 32]]) -> AccountProofBundle {
+        AccountProofBundle {
+            address,
+            account_proof: self.account_proof(address),
+            storage_proofs: slots
+                .iter()
+                .map(|slot| self.storage_proof(address, *slot))
+                .collect(),
+        }
+    }
+}
+
+// Post merge header with every field up to Prague
+pub fn test_header(parent_hash: H256, number: u64, state_root: H256) -> BlockHeader {
+    BlockHeader {
+        parent_hash,
+        // keccak256 of the empty ommers list
+        ommers_hash: keccak256(&[0xc0]),
+        beneficiary: H160::zero(),
+        state_root,
+        transactions_root: EMPTY_TRIE_ROOT,
+        receipts_root: EMPTY_TRIE_ROOT,
+        logs_bloom: Bloom::zero(),
+        difficulty: U256::zero(),
+        number,
+        gas_limit: 36_000_000,
+        gas_used: 12_345_678,
+        timestamp: 1_746_612_311 + number * 12,
+        extra_data: b"risc_zero_banking".to_vec(),
+        mix_hash: H256::repeat_byte(0x07),
+        nonce: H64::zero(),
+        base_fee_per_gas: Some(U256::from(1_000_000_000u64)),
+        withdrawals_root: Some(H256::repeat_byte(0x01)),
+        blob_gas_used: Some(131_072),
+        excess_blob_gas: Some(0),
+        parent_beacon_block_root: Some(H256::repeat_byte(0x02)),
+        requests_hash: Some(H256::repeat_byte(0x03)),
+    }
+}
+
+// RLP encoded headers of the blocks `first..first + length` with `state_root`, each one
+// the parent of the next. The last one is the checkpoint.
+pub fn header_chain(first: u64, length: u64, state_root: H256) -> Vec<Vec<u8>> {
+    let mut raw_headers = Vec::new();
+    let mut parent_hash = H256::repeat_byte(0xaa);
+    for number in first..first + length {
+        let header = test_header(parent_hash, number, state_root);
+        parent_hash = header.hash();
+        raw_headers.push(rlp::encode(&header).to_vec());
+    }
+    raw_headers
+}
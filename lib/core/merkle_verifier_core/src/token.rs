@@ -0,0 +1,254 @@
+extern crate alloc;
+use crate::bundle::AccountProofBundle;
+use crate::merkle_patricia::{verify_storage_proofs, ProofError, StorageProof, StorageValues};
+use crate::storage_layout::{address_key, mapping_slot};
+use alloc::vec;
+use alloc::vec::Vec;
+use ethereum_types::{H256, U256, U512};
+
+// ERC-20 balances proven from the storage of the token contract.
+//
+// `balanceOf` is not part of the state, only the storage slots behind it are. A profile
+// says where a token keeps the balance of a holder and how to read it; the host requests
+// the proof of `balance_slot(holder)`, the guest derives the same slot and decodes the
+// proven value. Tokens behind a proxy (USDC, stETH) keep their storage in the proxy, so
+// the profile is that of the proxy address with the layout of the implementation.
+
+// Decimals every balance is normalized to
+pub const NORMALIZED_DECIMALS: u8 = 18;
+
+// Where the balance of a holder is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceLayout {
+    // `mapping(address => uint256)` declared at `slot`
+    Mapping { slot: u64 },
+    // Mapping whose values hold the balance in the low `bits`, the bits above are flags.
+    // FiatTokenV2_2 (USDC) keeps the blacklist flag in the top bit of the balance.
+    PackedMapping { slot: u64, bits: u32 },
+    // Mapping of shares of a pool whose size changes (rebasing tokens like stETH). The
+    // balance is the holder's share of the pooled amount, see `ShareRate`.
+    Shares { slot: u64 },
+}
+
+// Pooled amount and total shares of a rebasing token, proven separately by the caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShareRate {
+    pub pooled: U256,
+    pub shares: U256,
+}
+
+impl ShareRate {
+    // Amount of `shares`, rounded down like the tokens do
+    pub fn amount(&self, shares: U256) -> Result<U256, ProofError> {
+        if self.shares.is_zero() {
+            return Err(ProofError::InvalidValue("Share rate without shares".into()));
+        }
+        let amount = shares.full_mul(self.pooled) / U512::from(self.shares);
+        U256::try_from(amount)
+            .map_err(|_| ProofError::InvalidValue("Share amount overflows".into()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenProfile {
+    pub address: [u8; 20],
+    pub symbol: &'static str,
+    pub decimals: u8,
+    pub layout: BalanceLayout,
+}
+
+// Proven balance of a holder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenBalance {
+    pub token: [u8; 20],
+    pub holder: [u8; 20],
+    // Amount in the token's own decimals
+    pub amount: U256,
+    pub decimals: u8,
+    // Amount with `NORMALIZED_DECIMALS` decimals
+    pub normalized: U256,
+}
+
+const fn hex_nibble(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        b'A'..=b'F' => c - b'A' + 10,
+        _ => panic!("Invalid hex digit"),
+    }
+}
+
+// Parse a 0x prefixed address at compile time
+pub(crate) const fn address(hex: &str) -> [u8; 20] {
+    let hex = hex.as_bytes();
+    assert!(hex.len() == 42, "Invalid address length");
+
+    let mut address = [0u8; 20];
+    let mut i = 0;
+    while i < 20 {
+        address[i] = (hex_nibble(hex[2 + 2 * i]) << 4) | hex_nibble(hex[3 + 2 * i]);
+        i += 1;
+    }
+    address
+}
+
+// Mainnet tokens accepted as collateral
+pub const TOKEN_PROFILES: &[TokenProfile] = &[
+    TokenProfile {
+        // FiatTokenProxy, `balanceAndBlacklistStates` of FiatTokenV2_2
+        address: address("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+        symbol: "USDC",
+        decimals: 6,
+        layout: BalanceLayout::PackedMapping { slot: 9, bits: 255 },
+    },
+    TokenProfile {
+        address: address("0xdAC17F958D2ee523a2206206994597C13D831ec7"),
+        symbol: "USDT",
+        decimals: 6,
+        layout: BalanceLayout::Mapping { slot: 2 },
+    },
+    TokenProfile {
+        address: address("0x6B175474E89094C44Da98b954EedeAC495271d0F"),
+        symbol: "DAI",
+        decimals: 18,
+        layout: BalanceLayout::Mapping { slot: 2 },
+    },
+    TokenProfile {
+        address: address("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+        symbol: "WETH",
+        decimals: 18,
+        layout: BalanceLayout::Mapping { slot: 3 },
+    },
+    TokenProfile {
+        address: address("0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599"),
+        symbol: "WBTC",
+        decimals: 8,
+        layout: BalanceLayout::Mapping { slot: 0 },
+    },
+    TokenProfile {
+        // Lido proxy, `shares` of StETH
+        address: address("0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84"),
+        symbol: "stETH",
+        decimals: 18,
+        layout: BalanceLayout::Shares { slot: 0 },
+    },
+    TokenProfile {
+        // Balances are Lido shares, see `staking`
+        address: address("0x7f39C581F595B53c5cb19bD0b3f8dA6c935E2Ca0"),
+        symbol: "wstETH",
+        decimals: 18,
+        layout: BalanceLayout::Mapping { slot: 0 },
+    },
+    TokenProfile {
+        // RocketTokenRETH, `_balances` follows the packed slot of RocketBase
+        address: address("0xae78736Cd615f374D3085123A210448E74Fc6393"),
+        symbol: "rETH",
+        decimals: 18,
+        layout: BalanceLayout::Mapping { slot: 1 },
+    },
+];
+
+pub fn token_profile(token: &[u8; 20]) -> Option<&'static TokenProfile> {
+    TOKEN_PROFILES
+        .iter()
+        .find(|profile| &profile.address == token)
+}
+
+// Scale an amount with `decimals` decimals to `NORMALIZED_DECIMALS`, rounding down
+pub fn normalize_amount(amount: U256, decimals: u8) -> Result<U256, ProofError> {
+    let ten = U256::from(10);
+    if decimals > NORMALIZED_DECIMALS {
+        return Ok(amount / ten.pow(U256::from(decimals - NORMALIZED_DECIMALS)));
+    }
+
+    amount
+        .checked_mul(ten.pow(U256::from(NORMALIZED_DECIMALS - decimals)))
+        .ok_or_else(|| ProofError::InvalidValue("Normalized amount overflows".into()))
+}
+
+impl TokenProfile {
+    // Slot holding the balance (or shares) of `holder`
+    pub fn balance_slot(&self, holder: &[u8; 20]) -> H256 {
+        let slot = match self.layout {
+            BalanceLayout::Mapping { slot }
+            | BalanceLayout::PackedMapping { slot, .. }
+            | BalanceLayout::Shares { slot } => slot,
+        };
+        mapping_slot(&address_key(holder), H256::from_low_u64_be(slot))
+    }
+
+    // Slots whose proofs the host has to request
+    pub fn required_slots(&self, holder: &[u8; 20]) -> Vec<[u8; 32]> {
+        vec![self.balance_slot(holder).into()]
+    }
+
+    // Decode the balance of `holder` from proven storage values of the token. Rebasing
+    // tokens need the `rate` of their pool.
+    pub fn balance_from_values(
+        &self,
+        holder: &[u8; 20],
+        values: &StorageValues,
+        rate: Option<&ShareRate>,
+    ) -> Result<TokenBalance, ProofError> {
+        let slot: [u8; 32] = self.balance_slot(holder).into();
+        let value = *values
+            .get(&slot)
+            .ok_or_else(|| ProofError::InvalidProof("Balance slot is not proven".into()))?;
+
+        let amount = match self.layout {
+            BalanceLayout::Mapping { .. } => value,
+            BalanceLayout::PackedMapping { bits, .. } => match bits {
+                1..=255 => value & ((U256::one() << bits as usize) - 1),
+                256 => value,
+                _ => return Err(ProofError::InvalidValue("Invalid balance width".into())),
+            },
+            BalanceLayout::Shares { .. } => {
+                let rate = rate.ok_or_else(|| {
+                    ProofError::InvalidValue("Share rate required for a rebasing token".into())
+                })?;
+                rate.amount(value)?
+            }
+        };
+
+        Ok(TokenBalance {
+            token: self.address,
+            holder: *holder,
+            amount,
+            decimals: self.decimals,
+            normalized: normalize_amount(amount, self.decimals)?,
+        })
+    }
+
+    // Verify the storage proofs against the token's proven storage root and decode the
+    // balance of `holder`
+    pub fn verify_balance(
+        &self,
+        storage_root: H256,
+        holder: &[u8; 20],
+        storage_proofs: &[StorageProof],
+        rate: Option<&ShareRate>,
+    ) -> Result<TokenBalance, ProofError> {
+        let values = verify_storage_proofs(storage_root, storage_proofs)?;
+        self.balance_from_values(holder, &values, rate)
+    }
+}
+
+// Verify the `eth_getProof` bundle of a registered token contract against the state root
+// and decode the balance of `holder`
+pub fn verify_token_balance(
+    state_root: H256,
+    bundle: &AccountProofBundle,
+    holder: &[u8; 20],
+    rate: Option<&ShareRate>,
+) -> Result<TokenBalance, ProofError> {
+    let profile = token_profile(&bundle.address)
+        .ok_or_else(|| ProofError::InvalidProof("Unknown token".into()))?;
+
+    let (account, values) = bundle.verify(state_root)?;
+    if account.is_none() {
+        return Err(ProofError::InvalidProof(
+            "Token contract does not exist".into(),
+        ));
+    }
+    profile.balance_from_values(holder, &values, rate)
+}
@@ -0,0 +1,199 @@
+// Node level diagnostics for proofs that fail to verify, e.g. because an RPC node returned
+// a proof for another block. Tracing hashes every proof node twice, it is meant for hosts
+// and tests, guests should use `verify_proof`.
+
+extern crate alloc;
+use crate::keccak::keccak256;
+use crate::merkle_patricia::{verify_sequential_proof, ProofError, ProofVerification};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use ethereum_types::H256;
+use rlp::Rlp;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeKind {
+    // Root of an empty trie
+    Empty,
+    Branch,
+    Extension,
+    Leaf,
+}
+
+// A node visited while following the key through the proof
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofStep {
+    // Position of the node in the proof, `None` for nodes embedded in their parent
+    pub proof_index: Option<usize>,
+    // `None` if the node could not be decoded
+    pub kind: Option<NodeKind>,
+    // Number of key nibbles matched before reaching the node
+    pub depth: usize,
+    // Key nibbles matched by the node
+    pub nibbles: Vec<u8>,
+    // Hash the parent refers to and hash of the proof node at `proof_index`, `None` for
+    // embedded nodes and for nodes missing from the proof
+    pub expected_hash: Option<H256>,
+    pub actual_hash: Option<H256>,
+}
+
+// Every step of a proof verification together with its outcome
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofTrace {
+    pub root_hash: H256,
+    pub key: Vec<u8>,
+    // Number of nodes in the proof, to spot unused ones
+    pub proof_len: usize,
+    pub steps: Vec<ProofStep>,
+    pub verification: ProofVerification,
+}
+
+// Error of an invalid proof with the steps leading up to it, the last step is the node
+// that failed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TracedProofError {
+    pub error: ProofError,
+    pub steps: Vec<ProofStep>,
+}
+
+// Kind of a proof node that was not decoded because its hash did not match
+fn node_kind(node: &[u8]) -> Option<NodeKind> {
+    let rlp = Rlp::new(node);
+    match rlp.item_count().ok()? {
+        17 => Some(NodeKind::Branch),
+        2 => match rlp.at(0).ok()?.data().ok()?.first()? >> 4 {
+            0x0 | 0x1 => Some(NodeKind::Extension),
+            0x2 | 0x3 => Some(NodeKind::Leaf),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Verify a proof like `verify_trie_proof` and record every node on the way
+pub fn trace_proof(root_hash: H256, key: &[u8], proof: &[Vec<u8>]) -> ProofTrace {
+    let mut steps = Vec::new();
+    let verification = verify_sequential_proof(root_hash, key, proof, Some(&mut steps));
+
+    for step in &mut steps {
+        if let Some(node) = step.proof_index.and_then(|index| proof.get(index)) {
+            step.actual_hash = Some(keccak256(node));
+            step.kind = step.kind.or_else(|| node_kind(node));
+        }
+    }
+
+    ProofTrace {
+        root_hash,
+        key: key.to_vec(),
+        proof_len: proof.len(),
+        steps,
+        verification,
+    }
+}
+
+// `verify_proof` returning the trace of invalid proofs with the error
+pub fn verify_proof_traced(
+    root_hash: H256,
+    key: &[u8],
+    proof: &[Vec<u8>],
+) -> Result<Option<Vec<u8>>, TracedProofError> {
+    let trace = trace_proof(root_hash, key, proof);
+    match trace.verification {
+        ProofVerification::Inclusion(value) => Ok(Some(value)),
+        ProofVerification::Exclusion(_) => Ok(None),
+        ProofVerification::Invalid(error) => Err(TracedProofError {
+            error,
+            steps: trace.steps,
+        }),
+    }
+}
+
+fn nibbles_to_hex(nibbles: &[u8]) -> String {
+    if nibbles.is_empty() {
+        return "-".into();
+    }
+    nibbles
+        .iter()
+        .filter_map(|&nibble| char::from_digit(nibble as u32, 16))
+        .collect()
+}
+
+impl fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            NodeKind::Empty => "empty",
+            NodeKind::Branch => "branch",
+            NodeKind::Extension => "extension",
+            NodeKind::Leaf => "leaf",
+        };
+        f.pad(name)
+    }
+}
+
+impl fmt::Display for ProofStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.proof_index {
+            Some(index) => write!(f, "node {:<4}", index)?,
+            None => write!(f, "embedded ")?,
+        }
+        match self.kind {
+            Some(kind) => write!(f, " {:<9}", kind)?,
+            None => write!(f, " {:<9}", "?")?,
+        }
+        write!(
+            f,
+            " depth {:<3} nibbles {:<8}",
+            self.depth,
+            nibbles_to_hex(&self.nibbles)
+        )?;
+
+        match (self.expected_hash, self.actual_hash) {
+            (Some(expected), Some(actual)) if expected == actual => {
+                write!(f, " hash {:?}", actual)
+            }
+            (Some(expected), Some(actual)) => {
+                write!(f, " expected {:?} actual {:?}", expected, actual)
+            }
+            (Some(expected), None) => write!(f, " expected {:?}, missing", expected),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for ProofTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Proof of key 0x{} against root {:?} ({} nodes)",
+            hex::encode(&self.key),
+            self.root_hash,
+            self.proof_len
+        )?;
+        for step in &self.steps {
+            writeln!(f, "  {}", step)?;
+        }
+
+        match &self.verification {
+            ProofVerification::Inclusion(value) => {
+                write!(f, "Inclusion, value of {} bytes", value.len())
+            }
+            ProofVerification::Exclusion(divergence) => write!(
+                f,
+                "Exclusion at depth {}: {:?}",
+                divergence.depth, divergence.reason
+            ),
+            ProofVerification::Invalid(error) => write!(f, "Invalid: {:?}", error),
+        }
+    }
+}
+
+impl fmt::Display for TracedProofError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:?}", self.error)?;
+        for step in &self.steps {
+            writeln!(f, "  {}", step)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,271 @@
+extern crate alloc;
+use crate::keccak::keccak256;
+use crate::merkle_patricia::{verify_proof, ProofError};
+use crate::receipt::tx_index_key;
+use alloc::vec::Vec;
+use ethereum_types::{H160, H256, U256};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use rlp::{Rlp, RlpStream};
+use serde::{Deserialize, Serialize};
+
+// EIP-2718 transaction types
+pub const LEGACY_TX_TYPE: u8 = 0x00;
+pub const ACCESS_LIST_TX_TYPE: u8 = 0x01;
+pub const DYNAMIC_FEE_TX_TYPE: u8 = 0x02;
+pub const BLOB_TX_TYPE: u8 = 0x03;
+
+// Signed transaction as included in a block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedTransaction {
+    pub tx_type: u8,
+    // `None` for legacy transactions signed without replay protection (pre EIP-155)
+    pub chain_id: Option<u64>,
+    pub nonce: u64,
+    // `None` for contract creations
+    pub to: Option<H160>,
+    pub value: U256,
+    pub input: Vec<u8>,
+    // Hash the sender signed
+    pub signing_hash: H256,
+    pub y_parity: bool,
+    pub r: U256,
+    pub s: U256,
+}
+
+// Transaction fields a lender cares about, with the sender recovered from the signature
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifiedTransaction {
+    pub hash: H256,
+    pub tx_type: u8,
+    pub chain_id: Option<u64>,
+    pub nonce: u64,
+    pub from: H160,
+    pub to: Option<H160>,
+    pub value: U256,
+    // First 4 bytes of the calldata, `None` for calls without a full selector
+    pub selector: Option<[u8; 4]>,
+}
+
+// Position of the fields shared by all transaction types in the RLP list
+struct Layout {
+    field_count: usize,
+    chain_id: Option<usize>,
+    nonce: usize,
+    to: usize,
+    value: usize,
+    input: usize,
+}
+
+fn layout(tx_type: u8) -> Result<Layout, ProofError> {
+    let layout = match tx_type {
+        // [nonce, gasPrice, gasLimit, to, value, data, v, r, s]
+        LEGACY_TX_TYPE => Layout {
+            field_count: 9,
+            chain_id: None,
+            nonce: 0,
+            to: 3,
+            value: 4,
+            input: 5,
+        },
+        // [chainId, nonce, gasPrice, gasLimit, to, value, data, accessList, yParity, r, s]
+        ACCESS_LIST_TX_TYPE => Layout {
+            field_count: 11,
+            chain_id: Some(0),
+            nonce: 1,
+            to: 4,
+            value: 5,
+            input: 6,
+        },
+        // [chainId, nonce, maxPriorityFeePerGas, maxFeePerGas, gasLimit, to, value, data,
+        //  accessList, yParity, r, s]
+        DYNAMIC_FEE_TX_TYPE => Layout {
+            field_count: 12,
+            chain_id: Some(0),
+            nonce: 1,
+            to: 5,
+            value: 6,
+            input: 7,
+        },
+        // [chainId, nonce, maxPriorityFeePerGas, maxFeePerGas, gasLimit, to, value, data,
+        //  accessList, maxFeePerBlobGas, blobVersionedHashes, yParity, r, s]
+        BLOB_TX_TYPE => Layout {
+            field_count: 14,
+            chain_id: Some(0),
+            nonce: 1,
+            to: 5,
+            value: 6,
+            input: 7,
+        },
+        _ => {
+            return Err(ProofError::RlpDecoding(
+                "Unsupported transaction type".into(),
+            ))
+        }
+    };
+    Ok(layout)
+}
+
+// Empty string for contract creations, 20 byte address otherwise
+fn decode_to(item: &Rlp) -> Result<Option<H160>, ProofError> {
+    let data = item.data()?;
+    match data.len() {
+        0 => Ok(None),
+        20 => Ok(Some(H160::from_slice(data))),
+        _ => Err(ProofError::RlpDecoding(
+            "Invalid transaction recipient".into(),
+        )),
+    }
+}
+
+// Decode a transaction as stored in the transaction trie: the RLP list for legacy
+// transactions, `tx_type || rlp(list)` for typed transactions
+pub fn decode_transaction(encoded: &[u8]) -> Result<SignedTransaction, ProofError> {
+    let (tx_type, payload) = match encoded.first() {
+        None => return Err(ProofError::RlpDecoding("Empty transaction".into())),
+        Some(&tx_type) if tx_type <= 0x7f => (tx_type, &encoded[1..]),
+        Some(_) => (LEGACY_TX_TYPE, encoded),
+    };
+    // A legacy transaction is never prefixed with its type
+    if tx_type == LEGACY_TX_TYPE && payload.len() != encoded.len() {
+        return Err(ProofError::RlpDecoding(
+            "Unsupported transaction type".into(),
+        ));
+    }
+
+    let layout = layout(tx_type)?;
+    let rlp = Rlp::new(payload);
+    if !rlp.is_list() || rlp.payload_info()?.total() != payload.len() {
+        return Err(ProofError::RlpDecoding(
+            "Invalid transaction encoding".into(),
+        ));
+    }
+    if rlp.item_count()? != layout.field_count {
+        return Err(ProofError::RlpDecoding(
+            "Invalid transaction field count".into(),
+        ));
+    }
+
+    let to = decode_to(&rlp.at(layout.to)?)?;
+    if tx_type == BLOB_TX_TYPE && to.is_none() {
+        return Err(ProofError::RlpDecoding(
+            "Blob transactions can not create contracts".into(),
+        ));
+    }
+
+    // The signature is always made of the last three fields
+    let signature_index = layout.field_count - 3;
+    let v: u64 = rlp.val_at(signature_index)?;
+    let r: U256 = rlp.val_at(signature_index + 1)?;
+    let s: U256 = rlp.val_at(signature_index + 2)?;
+
+    // The signed payload is the list without the signature, legacy transactions with
+    // replay protection (EIP-155) append `chainId, 0, 0` instead
+    let (chain_id, y_parity, extra_fields) = match layout.chain_id {
+        Some(index) => {
+            let y_parity = match v {
+                0 => false,
+                1 => true,
+                _ => return Err(ProofError::RlpDecoding("Invalid signature parity".into())),
+            };
+            (Some(rlp.val_at::<u64>(index)?), y_parity, 0)
+        }
+        None => match v {
+            27 | 28 => (None, v == 28, 0),
+            35.. => (Some((v - 35) / 2), (v - 35) % 2 == 1, 3),
+            _ => return Err(ProofError::RlpDecoding("Invalid signature v value".into())),
+        },
+    };
+
+    let mut stream = RlpStream::new_list(signature_index + extra_fields);
+    for index in 0..signature_index {
+        stream.append_raw(rlp.at(index)?.as_raw(), 1);
+    }
+    if extra_fields > 0 {
+        stream.append(&chain_id.unwrap_or_default());
+        stream.append_empty_data();
+        stream.append_empty_data();
+    }
+
+    let mut signing_payload = Vec::new();
+    if tx_type != LEGACY_TX_TYPE {
+        signing_payload.push(tx_type);
+    }
+    signing_payload.extend_from_slice(&stream.out());
+
+    Ok(SignedTransaction {
+        tx_type,
+        chain_id,
+        nonce: rlp.val_at(layout.nonce)?,
+        to,
+        value: rlp.val_at(layout.value)?,
+        input: rlp.val_at(layout.input)?,
+        signing_hash: keccak256(&signing_payload),
+        y_parity,
+        r,
+        s,
+    })
+}
+
+impl SignedTransaction {
+    // Recover the sender address from the signature. High `s` values are rejected
+    // (EIP-2), so a transaction has exactly one valid signature.
+    pub fn recover_sender(&self) -> Result<H160, ProofError> {
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        self.r.to_big_endian(&mut r);
+        self.s.to_big_endian(&mut s);
+
+        let signature = Signature::from_scalars(r, s)
+            .map_err(|_| ProofError::InvalidProof("Invalid transaction signature".into()))?;
+        if signature.normalize_s().is_some() {
+            return Err(ProofError::InvalidProof(
+                "Non canonical transaction signature".into(),
+            ));
+        }
+
+        let verifying_key = VerifyingKey::recover_from_prehash(
+            self.signing_hash.as_bytes(),
+            &signature,
+            RecoveryId::new(self.y_parity, false),
+        )
+        .map_err(|_| ProofError::InvalidProof("Failed to recover transaction sender".into()))?;
+
+        // Ethereum address: last 20 bytes of keccak256(uncompressed public key without prefix)
+        let public_key = verifying_key.to_encoded_point(false);
+        let hash = keccak256(&public_key.as_bytes()[1..]);
+        Ok(H160::from_slice(&hash[12..]))
+    }
+
+    pub fn selector(&self) -> Option<[u8; 4]> {
+        <[u8; 4]>::try_from(self.input.get(..4)?).ok()
+    }
+}
+
+// Decode a transaction and recover its sender
+pub fn verify_transaction(encoded: &[u8]) -> Result<VerifiedTransaction, ProofError> {
+    let transaction = decode_transaction(encoded)?;
+
+    Ok(VerifiedTransaction {
+        hash: keccak256(encoded),
+        tx_type: transaction.tx_type,
+        chain_id: transaction.chain_id,
+        nonce: transaction.nonce,
+        from: transaction.recover_sender()?,
+        to: transaction.to,
+        value: transaction.value,
+        selector: transaction.selector(),
+    })
+}
+
+// Verify the transaction at `tx_index` against a block's transactions root.
+// Returns `None` if the block has no transaction at that index.
+pub fn verify_transaction_proof(
+    transactions_root: H256,
+    tx_index: u64,
+    proof: &[Vec<u8>],
+) -> Result<Option<VerifiedTransaction>, ProofError> {
+    match verify_proof(transactions_root, &tx_index_key(tx_index), proof)? {
+        Some(encoded) => Ok(Some(verify_transaction(&encoded)?)),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,173 @@
+extern crate alloc;
+use crate::block_header::{header_hash, verify_header_chain};
+use crate::bundle::AccountProofBundle;
+use crate::framed::{FrameReader, FrameWriter};
+use crate::merkle_patricia::ProofError;
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use ethereum_types::{H256, U256};
+use serde::{Deserialize, Serialize};
+
+// Wallet age from historical state: an account whose nonce is above zero at some block has
+// sent a transaction (or, for a contract, been created) at or before that block. Its
+// proven state at an early block is a lower bound of its age.
+//
+// Every proof is anchored like the account proof guest: the headers lead from the early
+// block up to a checkpoint, whose hash the verifier of the receipt has to trust. Early
+// blocks are far out of reach of `blockhash` (256 blocks) and EIP-2935 (8191 blocks), and
+// a header chain up to a recent block would be millions of headers long. So the checkpoint
+// is either a recent block, checked through EIP-2935 while it is in the window, or comes
+// from a registry of trusted historical hashes (e.g. a checkpoint registry contract or the
+// epoch accumulators of era files). The chain to it is at most `HISTORY_SERVE_WINDOW`
+// headers long.
+
+// Blocks served by the EIP-2935 history contract, and the longest header chain a proof
+// may carry
+pub const HISTORY_SERVE_WINDOW: u64 = 8191;
+
+// Block hash the verifier trusts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub number: u64,
+    pub hash: H256,
+}
+
+// Account proof at an early block, with the header chain to its checkpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgeProof {
+    // RLP encoded headers from the early block up to the checkpoint block
+    pub headers: Vec<Vec<u8>>,
+    pub checkpoint_hash: [u8; 32],
+    pub account: AccountProofBundle,
+}
+
+// Proven lower bound of the age of an account: it was active at this block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActiveSince {
+    pub address: [u8; 20],
+    pub block_number: u64,
+    pub block_hash: H256,
+    pub timestamp: u64,
+    pub nonce: U256,
+    pub checkpoint_number: u64,
+    pub checkpoint_hash: H256,
+}
+
+impl AgeProof {
+    pub fn write(&self, writer: &mut FrameWriter) {
+        writer
+            .list(&self.headers)
+            .fixed(&self.checkpoint_hash)
+            .bundle(&self.account);
+    }
+
+    pub fn read(reader: &mut FrameReader) -> Result<Self, ProofError> {
+        Ok(AgeProof {
+            headers: reader.list()?,
+            checkpoint_hash: reader.fixed::<32>()?,
+            account: reader.bundle()?,
+        })
+    }
+
+    // Verify the header chain, then the account against the state root of the early block
+    pub fn verify(&self) -> Result<ActiveSince, ProofError> {
+        if self.headers.len() as u64 > HISTORY_SERVE_WINDOW + 1 {
+            return Err(ProofError::InvalidProof(
+                "Checkpoint is too far from the proven block".into(),
+            ));
+        }
+        let checkpoint_hash = H256::from(self.checkpoint_hash);
+        let header = verify_header_chain(&self.headers, checkpoint_hash)?;
+
+        let (account, _) = self.account.verify(header.state_root)?;
+        let nonce = match account {
+            Some(account) if !account.nonce.is_zero() => account.nonce,
+            _ => {
+                return Err(ProofError::InvalidProof(
+                    "Account was not active at the block".into(),
+                ))
+            }
+        };
+
+        Ok(ActiveSince {
+            address: self.account.address,
+            block_number: header.number,
+            block_hash: header_hash(&self.headers[0]),
+            timestamp: header.timestamp,
+            nonce,
+            checkpoint_number: header.number + self.headers.len() as u64 - 1,
+            checkpoint_hash,
+        })
+    }
+}
+
+// Framed encoding of the proofs of all owned accounts, the input of the wallet age guest
+pub fn encode_age_proofs(proofs: &[AgeProof]) -> Vec<u8> {
+    let mut writer = FrameWriter::new();
+    writer.u32(u32::try_from(proofs.len()).expect("Too many age proofs"));
+    for proof in proofs {
+        proof.write(&mut writer);
+    }
+    writer.finish()
+}
+
+pub fn decode_age_proofs(data: &[u8]) -> Result<Vec<AgeProof>, ProofError> {
+    let mut reader = FrameReader::new(data);
+    let count = reader.u32()?;
+    let proofs = (0..count)
+        .map(|_| AgeProof::read(&mut reader))
+        .collect::<Result<Vec<_>, _>>()?;
+    reader.finish()?;
+    Ok(proofs)
+}
+
+// Verify the proofs of several accounts, one per account
+pub fn verify_wallet_ages(proofs: &[AgeProof]) -> Result<Vec<ActiveSince>, ProofError> {
+    let mut addresses = BTreeSet::new();
+    proofs
+        .iter()
+        .map(|proof| {
+            if !addresses.insert(proof.account.address) {
+                return Err(ProofError::InvalidProof(
+                    "Duplicate account in age proofs".into(),
+                ));
+            }
+            proof.verify()
+        })
+        .collect()
+}
+
+// Block to anchor a proof of block `number` to: the first trusted checkpoint at or after
+// it within the window, otherwise `latest` if the block is recent enough
+pub fn checkpoint_for(number: u64, latest: u64, trusted: &[Checkpoint]) -> Option<u64> {
+    let in_window =
+        |checkpoint: u64| checkpoint >= number && checkpoint - number <= HISTORY_SERVE_WINDOW;
+    trusted
+        .iter()
+        .map(|checkpoint| checkpoint.number)
+        .filter(|&checkpoint| in_window(checkpoint))
+        .min()
+        .or_else(|| in_window(latest).then_some(latest))
+}
+
+// Every proof is anchored to one of the trusted checkpoints
+pub fn check_checkpoints(ages: &[ActiveSince], trusted: &[Checkpoint]) -> Result<(), ProofError> {
+    for age in ages {
+        let checkpoint = Checkpoint {
+            number: age.checkpoint_number,
+            hash: age.checkpoint_hash,
+        };
+        if !trusted.contains(&checkpoint) {
+            return Err(ProofError::InvalidProof(
+                "Age proof anchored to an untrusted checkpoint".into(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// The oldest of the proven accounts, the age of the wallet as a whole
+pub fn earliest_active(ages: &[ActiveSince]) -> Option<&ActiveSince> {
+    ages.iter()
+        .min_by_key(|age| (age.timestamp, age.block_number))
+}
@@ -0,0 +1,132 @@
+#[cfg(test)]
+mod tests {
+    use ethereum_types::{Bloom, H160, H256, H64, U256};
+    use merkle_verifier_core::block_header::*;
+    use merkle_verifier_core::merkle_patricia::EMPTY_TRIE_ROOT;
+    use merkle_verifier_core::test_utils::{header_chain, test_header};
+
+    fn h256(hex_str: &str) -> H256 {
+        H256::from_slice(&hex::decode(hex_str).unwrap())
+    }
+
+    // Mainnet genesis block
+    fn genesis() -> BlockHeader {
+        BlockHeader {
+            parent_hash: H256::zero(),
+            ommers_hash: h256("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"),
+            beneficiary: H160::zero(),
+            state_root: h256("d7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544"),
+            transactions_root: EMPTY_TRIE_ROOT,
+            receipts_root: EMPTY_TRIE_ROOT,
+            logs_bloom: Bloom::zero(),
+            difficulty: U256::from(0x400000000u64),
+            number: 0,
+            gas_limit: 5000,
+            gas_used: 0,
+            timestamp: 0,
+            extra_data: hex::decode(
+                "11bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cbdb7a38e1e50b1b82fa",
+            )
+            .unwrap(),
+            mix_hash: H256::zero(),
+            nonce: H64::from_low_u64_be(0x42),
+            base_fee_per_gas: None,
+            withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
+            requests_hash: None,
+        }
+    }
+
+    fn chain(length: u64) -> Vec<Vec<u8>> {
+        header_chain(100, length, EMPTY_TRIE_ROOT)
+    }
+
+    #[test]
+    fn test_genesis_hash() {
+        assert_eq!(
+            genesis().hash(),
+            h256("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3")
+        );
+    }
+
+    #[test]
+    fn test_header_roundtrip_for_every_fork() {
+        let prague = test_header(H256::repeat_byte(0xaa), 1, EMPTY_TRIE_ROOT);
+        let cancun = BlockHeader {
+            requests_hash: None,
+            ..prague.clone()
+        };
+        let shanghai = BlockHeader {
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
+            ..cancun.clone()
+        };
+        let london = BlockHeader {
+            withdrawals_root: None,
+            ..shanghai.clone()
+        };
+
+        for header in [genesis(), london, shanghai, cancun, prague] {
+            let raw = rlp::encode(&header);
+            assert_eq!(decode_header(&raw).unwrap(), header);
+        }
+    }
+
+    #[test]
+    fn test_decode_header_rejects_malformed_input() {
+        let mut raw = rlp::encode(&test_header(H256::zero(), 1, EMPTY_TRIE_ROOT)).to_vec();
+
+        // Trailing bytes
+        raw.push(0x00);
+        assert!(decode_header(&raw).is_err());
+        raw.pop();
+
+        // Truncated
+        assert!(decode_header(&raw[..raw.len() - 1]).is_err());
+
+        // Not a list
+        assert!(decode_header(&rlp::encode(&H256::zero())).is_err());
+    }
+
+    #[test]
+    fn test_verify_header_chain() {
+        let raw_headers = chain(4);
+        let checkpoint_hash = decode_header(&raw_headers[3]).unwrap().hash();
+
+        let proven = verify_header_chain(&raw_headers, checkpoint_hash).unwrap();
+        assert_eq!(proven, decode_header(&raw_headers[0]).unwrap());
+
+        // The checkpoint block on its own
+        let proven = verify_header_chain(&raw_headers[3..], checkpoint_hash).unwrap();
+        assert_eq!(proven.number, 103);
+    }
+
+    #[test]
+    fn test_verify_header_chain_rejects_broken_chains() {
+        let raw_headers = chain(4);
+        let checkpoint_hash = decode_header(&raw_headers[3]).unwrap().hash();
+
+        // Chain not ending at the checkpoint
+        assert!(verify_header_chain(&raw_headers[..3], checkpoint_hash).is_err());
+
+        // Missing link
+        let gap = vec![
+            raw_headers[0].clone(),
+            raw_headers[2].clone(),
+            raw_headers[3].clone(),
+        ];
+        assert!(verify_header_chain(&gap, checkpoint_hash).is_err());
+
+        // Tampered state root of the proven block
+        let mut forged = decode_header(&raw_headers[0]).unwrap();
+        forged.state_root = H256::repeat_byte(0xff);
+        let mut tampered = raw_headers.clone();
+        tampered[0] = rlp::encode(&forged).to_vec();
+        assert!(verify_header_chain(&tampered, checkpoint_hash).is_err());
+
+        assert!(verify_header_chain(&[], checkpoint_hash).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use ethereum_types::{H256, U256};
+    use merkle_verifier_core::bundle::AccountProofBundle;
+    use merkle_verifier_core::merkle_patricia::StorageProof;
+    use sha3::{Digest, Keccak256};
+
+    const USER_HISTORY_PROOF: &str = include_str!(
+        "../../../test_data/user_history_merkle_proof/user_history_proof_e7f1…051.json"
+    );
+
+    fn decode_hex(value: &serde_json::Value) -> Vec<u8> {
+        hex::decode(value.as_str().unwrap().trim_start_matches("0x")).unwrap()
+    }
+
+    fn rpc_response() -> serde_json::Value {
+        let json: serde_json::Value = serde_json::from_str(USER_HISTORY_PROOF).unwrap();
+        json["merkle_proof"].clone()
+    }
+
+    fn hex_nodes(nodes: &serde_json::Value) -> Vec<Vec<u8>> {
+        nodes.as_array().unwrap().iter().map(decode_hex).collect()
+    }
+
+    // Bundle built by hand from the eth_getProof response
+    fn bundle() -> AccountProofBundle {
+        let response = rpc_response();
+        AccountProofBundle {
+            address: decode_hex(&response["address"]).try_into().unwrap(),
+            account_proof: hex_nodes(&response["accountProof"]),
+            storage_proofs: response["storageProof"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|storage_proof| StorageProof {
+                    key: decode_hex(&storage_proof["key"]).try_into().unwrap(),
+                    proof: hex_nodes(&storage_proof["proof"]),
+                })
+                .collect(),
+        }
+    }
+
+    fn state_root(bundle: &AccountProofBundle) -> H256 {
+        H256::from_slice(&Keccak256::digest(&bundle.account_proof[0]))
+    }
+
+    #[test]
+    fn test_verify_bundle() {
+        let bundle = bundle();
+
+        let (account, values) = bundle.verify(state_root(&bundle)).unwrap();
+        assert!(account.is_some());
+        assert_eq!(
+            values.values().copied().collect::<Vec<_>>(),
+            vec![U256::from(1), U256::from(2), U256::from(3)]
+        );
+    }
+
+    #[test]
+    fn test_bundle_serde_roundtrip() {
+        let bundle = bundle();
+
+        let json = serde_json::to_string(&bundle).unwrap();
+        assert_eq!(
+            serde_json::from_str::<AccountProofBundle>(&json).unwrap(),
+            bundle
+        );
+    }
+
+    #[cfg(feature = "ethers")]
+    #[test]
+    fn test_bundle_from_eip1186_response() {
+        use ethers_core::types::EIP1186ProofResponse;
+
+        let response: EIP1186ProofResponse = serde_json::from_value(rpc_response()).unwrap();
+
+        assert_eq!(AccountProofBundle::from(&response), bundle());
+        assert_eq!(AccountProofBundle::from(response), bundle());
+    }
+}
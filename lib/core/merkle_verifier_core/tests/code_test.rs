@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use ethereum_types::U256;
+    use merkle_verifier_core::code::*;
+    use merkle_verifier_core::keccak::keccak256;
+    use merkle_verifier_core::merkle_patricia::{verify_account_proof, AccountData, ProofError};
+    use merkle_verifier_core::test_utils::StateBuilder;
+
+    const LENDING: [u8; 20] = [0xe7; 20];
+    const OWNER: [u8; 20] = [0xf3; 20];
+    // PUSH1 0 PUSH1 0 RETURN
+    const LENDING_CODE: [u8; 5] = [0x60, 0x00, 0x60, 0x00, 0xf3];
+
+    // Proven accounts of a state with a Lending deployment and its owner
+    fn proven_accounts() -> (AccountData, AccountData) {
+        let mut state = StateBuilder::new();
+        state
+            .account(LENDING, 1, U256::zero())
+            .code(LENDING, &LENDING_CODE)
+            .account(OWNER, 12, U256::from(10).pow(U256::from(18)));
+        let state_root = state.state_root();
+
+        let prove = |address: [u8; 20]| {
+            verify_account_proof(state_root, &address, &state.account_proof(address))
+                .unwrap()
+                .unwrap()
+        };
+        (prove(LENDING), prove(OWNER))
+    }
+
+    #[test]
+    fn test_empty_code_hash() {
+        assert_eq!(keccak256(&[]), EMPTY_CODE_HASH);
+    }
+
+    #[test]
+    fn test_verify_code() {
+        let (lending, owner) = proven_accounts();
+        let code_hash = keccak256(&LENDING_CODE);
+
+        assert_eq!(verify_code(&lending, &LENDING_CODE, None), Ok(code_hash));
+        assert_eq!(
+            verify_code(
+                &lending,
+                &LENDING_CODE,
+                Some(&[keccak256(b"v1"), code_hash])
+            ),
+            Ok(code_hash)
+        );
+        assert_eq!(verify_code(&owner, &[], None), Ok(EMPTY_CODE_HASH));
+
+        assert!(matches!(
+            verify_code(&lending, &LENDING_CODE[..4], None),
+            Err(ProofError::HashMismatch(_))
+        ));
+        assert!(matches!(
+            verify_code(&lending, &LENDING_CODE, Some(&[keccak256(b"v1")])),
+            Err(ProofError::InvalidProof(_))
+        ));
+        assert!(verify_code(&lending, &LENDING_CODE, Some(&[])).is_err());
+    }
+
+    #[test]
+    fn test_verify_eoa() {
+        let (lending, owner) = proven_accounts();
+
+        assert!(owner.is_eoa());
+        assert_eq!(verify_eoa(&owner), Ok(()));
+        assert!(!lending.is_eoa());
+        assert!(verify_eoa(&lending).is_err());
+
+        // EIP-7702 delegation designator
+        let mut delegated = owner.clone();
+        let mut designator = vec![0xef, 0x01, 0x00];
+        designator.extend_from_slice(&LENDING);
+        delegated.code_hash = keccak256(&designator);
+        assert!(!delegated.is_eoa());
+    }
+}
@@ -0,0 +1,187 @@
+#[cfg(test)]
+mod tests {
+    use ethereum_types::{H256, U256};
+    use merkle_verifier_core::bundle::AccountProofBundle;
+    use merkle_verifier_core::framed::*;
+    use merkle_verifier_core::keccak::keccak256;
+    use merkle_verifier_core::merkle_patricia::{AccountData, ProofError, StorageProof};
+    use merkle_verifier_core::test_utils::StateBuilder;
+
+    const USER_HISTORY_PROOF: &str = include_str!(
+        "../../../test_data/user_history_merkle_proof/user_history_proof_e7f1…051.json"
+    );
+
+    fn decode_hex(value: &serde_json::Value) -> Vec<u8> {
+        hex::decode(value.as_str().unwrap().trim_start_matches("0x")).unwrap()
+    }
+
+    fn hex_nodes(nodes: &serde_json::Value) -> Vec<Vec<u8>> {
+        nodes.as_array().unwrap().iter().map(decode_hex).collect()
+    }
+
+    fn fixture_bundle() -> AccountProofBundle {
+        let json: serde_json::Value = serde_json::from_str(USER_HISTORY_PROOF).unwrap();
+        let response = &json["merkle_proof"];
+        AccountProofBundle {
+            address: decode_hex(&response["address"]).try_into().unwrap(),
+            account_proof: hex_nodes(&response["accountProof"]),
+            storage_proofs: response["storageProof"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|storage_proof| StorageProof {
+                    key: decode_hex(&storage_proof["key"]).try_into().unwrap(),
+                    proof: hex_nodes(&storage_proof["proof"]),
+                })
+                .collect(),
+        }
+    }
+
+    fn slot(index: u64) -> [u8; 32] {
+        H256::from_low_u64_be(index).into()
+    }
+
+    // The decoded bundle holds the node table and the storage keys, and proves the same
+    // values as the encoded one
+    fn assert_decodes_to(decoded: &AccountProofBundle, bundle: &AccountProofBundle, root: H256) {
+        assert_eq!(decoded.address, bundle.address);
+        let keys = |bundle: &AccountProofBundle| -> Vec<[u8; 32]> {
+            bundle
+                .storage_proofs
+                .iter()
+                .map(|storage| storage.key)
+                .collect()
+        };
+        assert_eq!(keys(decoded), keys(bundle));
+        assert!(decoded
+            .storage_proofs
+            .iter()
+            .all(|storage| storage.proof.is_empty()));
+        let (account, values) = decoded.verify(root).unwrap();
+        let (expected_account, expected_values) = bundle.verify(root).unwrap();
+        let fields = |account: Option<AccountData>| {
+            account.map(|account| {
+                (
+                    account.nonce,
+                    account.balance,
+                    account.storage_root,
+                    account.code_hash,
+                )
+            })
+        };
+        assert_eq!(fields(account), fields(expected_account));
+        assert_eq!(values, expected_values);
+    }
+
+    #[test]
+    fn test_bundle_roundtrip() {
+        let bundle = fixture_bundle();
+        let root = keccak256(&bundle.account_proof[0]);
+        let encoded = encode_bundle(&bundle);
+        let decoded = decode_bundle(&encoded).unwrap();
+        assert_decodes_to(&decoded, &bundle, root);
+
+        // Storage proofs of the same account share nodes, the table holds them once
+        let node_bytes: usize = bundle
+            .storage_proofs
+            .iter()
+            .flat_map(|storage| &storage.proof)
+            .chain(&bundle.account_proof)
+            .map(Vec::len)
+            .sum();
+        assert!(encoded.len() < node_bytes);
+        let table_bytes: usize = decoded.account_proof.iter().map(Vec::len).sum();
+        assert!(table_bytes < node_bytes);
+
+        // Bundles without storage proofs, and of missing accounts
+        let mut state = StateBuilder::new();
+        state.account([1; 20], 1, U256::from(10));
+        for address in [[1; 20], [2; 20]] {
+            for slots in [&[][..], &[slot(1)]] {
+                let bundle = state.bundle(address, slots);
+                let decoded = decode_bundle(&encode_bundle(&bundle)).unwrap();
+                assert_decodes_to(&decoded, &bundle, state.state_root());
+            }
+        }
+    }
+
+    #[test]
+    fn test_reader_and_writer_agree() {
+        let bundle = fixture_bundle();
+        let headers = vec![vec![0xc0], vec![0xf8; 600], Vec::new()];
+
+        let encoded = FrameWriter::new()
+            .list(&headers)
+            .fixed(&[7; 32])
+            .bundle(&bundle)
+            .u32(42)
+            .finish();
+
+        let mut reader = FrameReader::new(&encoded);
+        assert_eq!(reader.list().unwrap(), headers);
+        assert_eq!(reader.fixed::<32>().unwrap(), [7; 32]);
+        assert_eq!(
+            reader.bundle().unwrap(),
+            decode_bundle(&encode_bundle(&bundle)).unwrap()
+        );
+        assert_eq!(reader.u32().unwrap(), 42);
+        assert!(reader.finish().is_ok());
+    }
+
+    #[test]
+    fn test_malformed_input_is_rejected() {
+        let mut state = StateBuilder::new();
+        for index in 1..=20u8 {
+            state.account([index; 20], 0, U256::from(index)).storage(
+                [index; 20],
+                slot(index as u64),
+                U256::from(1),
+            );
+        }
+        let bundle = state.bundle([3; 20], &[slot(3), slot(4)]);
+        let encoded = encode_bundle(&bundle);
+
+        // Every strict prefix is truncated
+        for len in 0..encoded.len() {
+            assert!(decode_bundle(&encoded[..len]).is_err());
+        }
+
+        // Trailing bytes
+        let mut extended = encoded.clone();
+        extended.push(0);
+        assert!(decode_bundle(&extended).is_err());
+
+        // A huge count must not allocate before the input runs out
+        let mut huge_count = encoded;
+        huge_count[20..24].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(decode_bundle(&huge_count).is_err());
+
+        // A table node on no proven path decodes, but does not verify
+        let mut extra = bundle.clone();
+        extra.storage_proofs[1].proof = state.storage_proof([5; 20], slot(5)).proof;
+        let decoded = decode_bundle(&encode_bundle(&extra)).unwrap();
+        assert_eq!(
+            decoded.verify(state.state_root()).unwrap_err(),
+            ProofError::InvalidProof("Proof contains unused nodes".into())
+        );
+    }
+
+    #[test]
+    fn test_repeated_nodes_are_stored_once() {
+        // One large node listed many times
+        let node = vec![0x42; 4096];
+        let bundle = AccountProofBundle {
+            address: [1; 20],
+            account_proof: vec![node.clone(); 1_000],
+            storage_proofs: vec![StorageProof {
+                key: slot(1),
+                proof: vec![node.clone(); 1_000],
+            }],
+        };
+
+        let encoded = encode_bundle(&bundle);
+        assert!(encoded.len() < node.len() + 100);
+        let decoded = decode_bundle(&encoded).unwrap();
+        assert_eq!(decoded.account_proof, vec![node]);
+    }
+}
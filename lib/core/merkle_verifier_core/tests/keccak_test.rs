@@ -0,0 +1,29 @@
+#[cfg(test)]
+mod tests {
+    use ethereum_types::H256;
+    use merkle_verifier_core::keccak::keccak256;
+    use merkle_verifier_core::merkle_patricia::EMPTY_TRIE_ROOT;
+    use sha3::{Digest, Keccak256};
+
+    #[test]
+    fn test_known_hashes() {
+        assert_eq!(
+            hex::encode(keccak256(&[])),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        // Root of the empty trie is the hash of the empty RLP string
+        assert_eq!(keccak256(&[0x80]), EMPTY_TRIE_ROOT);
+    }
+
+    #[test]
+    fn test_matches_sha3_across_block_boundaries() {
+        // Keccak256 absorbs 136 bytes per permutation
+        for length in [1, 31, 32, 135, 136, 137, 272, 1000] {
+            let data: Vec<u8> = (0..length).map(|i| i as u8).collect();
+            assert_eq!(
+                keccak256(&data),
+                H256::from_slice(&Keccak256::digest(&data))
+            );
+        }
+    }
+}
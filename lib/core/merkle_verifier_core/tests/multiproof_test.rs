@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod tests {
+    use ethereum_types::{H256, U256};
+    use merkle_verifier_core::merkle_patricia::*;
+    use merkle_verifier_core::multiproof::*;
+    use rlp::RlpStream;
+    use sha3::{Digest, Keccak256};
+
+    fn keccak(data: &[u8]) -> [u8; 32] {
+        Keccak256::digest(data).into()
+    }
+
+    fn account_rlp(balance: u64) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(4);
+        stream
+            .append(&1u64)
+            .append(&U256::from(balance))
+            .append(&EMPTY_TRIE_ROOT)
+            .append(&H256::from(keccak(&[])));
+        stream.out().to_vec()
+    }
+
+    // Leaf holding the rest of the key after the first nibble (63 nibbles, odd)
+    fn account_leaf(address_hash: &[u8; 32], value: &[u8]) -> Vec<u8> {
+        let nibbles = encode_path(address_hash);
+        let mut path = vec![0x30 | nibbles[1]];
+        for pair in nibbles[2..].chunks(2) {
+            path.push((pair[0] << 4) | pair[1]);
+        }
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&path).append(&value.to_vec());
+        stream.out().to_vec()
+    }
+
+    struct StateTrie {
+        root: H256,
+        accounts: Vec<([u8; 20], Vec<Vec<u8>>)>,
+        // Address that is not in the trie, its first nibble has no branch child
+        missing: [u8; 20],
+    }
+
+    // State trie with accounts whose address hashes all start with a different nibble,
+    // so the root is a branch with one leaf per account
+    fn state_trie(count: usize) -> StateTrie {
+        let mut children: Vec<Option<Vec<u8>>> = vec![None; 16];
+        let mut addresses = Vec::new();
+        let mut missing = None;
+
+        for byte in 0u8..=255 {
+            let address = [byte; 20];
+            let address_hash = keccak(&address);
+            let nibble = (address_hash[0] >> 4) as usize;
+            if children[nibble].is_some() {
+                continue;
+            }
+            if addresses.len() == count {
+                missing = Some(address);
+                break;
+            }
+            let balance = 1_000 * (addresses.len() as u64 + 1);
+            children[nibble] = Some(account_leaf(&address_hash, &account_rlp(balance)));
+            addresses.push(address);
+        }
+
+        let mut stream = RlpStream::new_list(17);
+        for child in &children {
+            match child {
+                Some(leaf) => stream.append(&keccak(leaf).to_vec()),
+                None => stream.append_empty_data(),
+            };
+        }
+        stream.append_empty_data();
+        let root = stream.out().to_vec();
+
+        let accounts = addresses
+            .into_iter()
+            .map(|address| {
+                let nibble = (keccak(&address)[0] >> 4) as usize;
+                let leaf = children[nibble].clone().unwrap();
+                (address, vec![root.clone(), leaf])
+            })
+            .collect();
+
+        StateTrie {
+            root: H256::from(keccak(&root)),
+            accounts,
+            missing: missing.unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_multiproof_matches_single_proofs() {
+        let trie = state_trie(3);
+
+        let accounts = verify_account_multiproof(trie.root, &trie.accounts).unwrap();
+
+        assert_eq!(accounts.len(), 3);
+        for ((address, proof), account) in trie.accounts.iter().zip(&accounts) {
+            let single = verify_account_proof(trie.root, address, proof).unwrap();
+            assert_eq!(
+                single.as_ref().map(|a| a.balance),
+                account.as_ref().map(|a| a.balance)
+            );
+        }
+        assert_eq!(accounts[2].as_ref().unwrap().balance, U256::from(3_000));
+    }
+
+    #[test]
+    fn test_shared_nodes_are_stored_once() {
+        let trie = state_trie(3);
+
+        // Three proofs of two nodes each, all sharing the root
+        let cache = NodeCache::from_proofs(trie.accounts.iter().map(|(_, p)| p.as_slice()));
+        assert_eq!(cache.len(), 4);
+    }
+
+    #[test]
+    fn test_multiproof_proves_missing_account() {
+        let trie = state_trie(2);
+        let mut accounts = trie.accounts.clone();
+        // The root alone proves that the branch has no child for the missing address
+        accounts.push((trie.missing, vec![trie.accounts[0].1[0].clone()]));
+
+        let result = verify_account_multiproof(trie.root, &accounts).unwrap();
+        assert!(result[0].is_some());
+        assert!(result[1].is_some());
+        assert!(result[2].is_none());
+    }
+
+    #[test]
+    fn test_multiproof_rejects_invalid_proofs() {
+        let trie = state_trie(3);
+
+        // A node that is not on any path
+        let mut extra = trie.accounts.clone();
+        extra[0].1.push(account_rlp(42));
+        assert!(verify_account_multiproof(trie.root, &extra).is_err());
+
+        // Missing leaf of the second account
+        let mut missing_leaf = trie.accounts.clone();
+        missing_leaf[1].1.pop();
+        assert!(verify_account_multiproof(trie.root, &missing_leaf).is_err());
+
+        // Wrong root
+        assert!(verify_account_multiproof(H256::repeat_byte(0x11), &trie.accounts).is_err());
+    }
+}
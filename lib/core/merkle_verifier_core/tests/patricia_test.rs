@@ -1,2 +1,514 @@
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use ethereum_types::{H256, U256};
+    use merkle_verifier_core::merkle_patricia::*;
+    use merkle_verifier_core::multiproof::NodeCache;
+    use merkle_verifier_core::storage_layout::{mapping_slot, uint_key};
+    use rlp::RlpStream;
+    use sha3::{Digest, Keccak256};
+
+    // eth_getProof response for the Lending contract (0xe7f1...0512) on Anvil, proving the
+    // UserHistory struct of 0xf39F...2266
+    const USER_HISTORY_PROOF: &str = include_str!(
+        "../../../test_data/user_history_merkle_proof/user_history_proof_e7f1…051.json"
+    );
+
+    // eth_getProof response for a small contract whose mapping leaves are embedded in their
+    // branch node
+    const INLINE_STORAGE_PROOF: &str =
+        include_str!("../../../test_data/inline_storage_proof/inline_storage_proof.json");
+
+    struct Fixture {
+        state_root: H256,
+        address: [u8; 20],
+        storage_root: H256,
+        account_proof: Vec<Vec<u8>>,
+        storage_proofs: Vec<StorageProof>,
+        storage_values: Vec<U256>,
+    }
+
+    fn decode_hex(value: &serde_json::Value) -> Vec<u8> {
+        hex::decode(value.as_str().unwrap().trim_start_matches("0x")).unwrap()
+    }
+
+    fn load_fixture() -> Fixture {
+        parse_fixture(USER_HISTORY_PROOF)
+    }
+
+    fn parse_fixture(fixture: &str) -> Fixture {
+        let json: serde_json::Value = serde_json::from_str(fixture).unwrap();
+        let proof = &json["merkle_proof"];
+
+        let account_proof: Vec<Vec<u8>> = proof["accountProof"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(decode_hex)
+            .collect();
+
+        let mut storage_proofs = Vec::new();
+        let mut storage_values = Vec::new();
+        for storage_proof in proof["storageProof"].as_array().unwrap() {
+            storage_proofs.push(StorageProof {
+                key: decode_hex(&storage_proof["key"]).try_into().unwrap(),
+                proof: storage_proof["proof"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(decode_hex)
+                    .collect(),
+            });
+            storage_values
+                .push(U256::from_str_radix(storage_proof["value"].as_str().unwrap(), 16).unwrap());
+        }
+
+        Fixture {
+            // The fixture has no block header, the root node of the account proof is the
+            // state root of that block
+            state_root: H256::from_slice(&Keccak256::digest(&account_proof[0])),
+            address: decode_hex(&proof["address"]).try_into().unwrap(),
+            storage_root: H256::from_slice(&decode_hex(&proof["storageHash"])),
+            account_proof,
+            storage_proofs,
+            storage_values,
+        }
+    }
+
+    #[test]
+    fn test_verify_account_proof() {
+        let fixture = load_fixture();
+
+        let account =
+            verify_account_proof(fixture.state_root, &fixture.address, &fixture.account_proof)
+                .unwrap()
+                .unwrap();
+
+        assert_eq!(account.storage_root, fixture.storage_root);
+    }
+
+    #[test]
+    fn test_verify_eth_proof_with_storage_slots() {
+        let fixture = load_fixture();
+
+        let (account, values) = verify_eth_proof(
+            fixture.state_root,
+            fixture.address,
+            &fixture.account_proof,
+            &fixture.storage_proofs,
+        )
+        .unwrap();
+
+        assert!(account.is_some());
+        assert_eq!(values.len(), 3);
+        for (storage_proof, expected) in fixture.storage_proofs.iter().zip(&fixture.storage_values)
+        {
+            assert_eq!(values[&storage_proof.key], *expected);
+        }
+    }
+
+    #[test]
+    fn test_verify_eth_proof_with_inline_storage_leaves() {
+        let fixture = parse_fixture(INLINE_STORAGE_PROOF);
+        let json: serde_json::Value = serde_json::from_str(INLINE_STORAGE_PROOF).unwrap();
+        assert_eq!(
+            fixture.state_root,
+            H256::from_slice(&decode_hex(&json["state_root"]))
+        );
+
+        // Slots of `values[72708]` and `values[79011]`, the mapping is at slot 1
+        for (storage_proof, key) in fixture.storage_proofs[1..].iter().zip([72708u64, 79011]) {
+            let slot = mapping_slot(&uint_key(U256::from(key)), H256::from_low_u64_be(1));
+            assert_eq!(storage_proof.key, slot.0);
+            // The leaf is embedded in the last node of the proof
+            let branch = storage_proof.proof.last().unwrap();
+            assert!(rlp::Rlp::new(branch)
+                .iter()
+                .any(|child| child.is_list() && child.as_raw().len() < 32));
+        }
+
+        let (account, values) = verify_eth_proof(
+            fixture.state_root,
+            fixture.address,
+            &fixture.account_proof,
+            &fixture.storage_proofs,
+        )
+        .unwrap();
+
+        assert_eq!(account.unwrap().storage_root, fixture.storage_root);
+        assert_eq!(
+            fixture.storage_values,
+            vec![U256::from(2), U256::from(42), U256::from(7)]
+        );
+        for (storage_proof, expected) in fixture.storage_proofs.iter().zip(&fixture.storage_values)
+        {
+            assert_eq!(values[&storage_proof.key], *expected);
+        }
+
+        // Tampering with an embedded leaf changes the hash of its branch
+        let mut tampered = fixture.storage_proofs.clone();
+        let branch = tampered[1].proof.last_mut().unwrap();
+        let last = branch.len() - 3;
+        branch[last] ^= 1;
+        assert!(verify_eth_proof(
+            fixture.state_root,
+            fixture.address,
+            &fixture.account_proof,
+            &tampered,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_verify_eth_proof_rejects_invalid_storage_proof() {
+        let fixture = load_fixture();
+
+        // Proof of the second slot presented for the first slot
+        let mut storage_proofs = fixture.storage_proofs.clone();
+        storage_proofs[0].proof = storage_proofs[1].proof.clone();
+        let result = verify_eth_proof(
+            fixture.state_root,
+            fixture.address,
+            &fixture.account_proof,
+            &storage_proofs,
+        );
+        assert!(result.is_err());
+
+        // Same slot twice
+        let duplicated = vec![
+            fixture.storage_proofs[0].clone(),
+            fixture.storage_proofs[0].clone(),
+        ];
+        assert!(verify_eth_proof(
+            fixture.state_root,
+            fixture.address,
+            &fixture.account_proof,
+            &duplicated,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_verify_eth_proof_rejects_wrong_state_root() {
+        let fixture = load_fixture();
+
+        assert!(verify_eth_proof(
+            H256::repeat_byte(0x11),
+            fixture.address,
+            &fixture.account_proof,
+            &fixture.storage_proofs,
+        )
+        .is_err());
+    }
+
+    // Hex prefix encoding of a nibble path
+    fn compact(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+        let flag = if leaf { 2 } else { 0 };
+        let mut encoded = Vec::new();
+        let rest = if nibbles.len() % 2 == 1 {
+            encoded.push(((flag + 1) << 4) | nibbles[0]);
+            &nibbles[1..]
+        } else {
+            encoded.push(flag << 4);
+            nibbles
+        };
+        for pair in rest.chunks(2) {
+            encoded.push((pair[0] << 4) | pair[1]);
+        }
+        encoded
+    }
+
+    fn leaf(nibbles: &[u8], value: &[u8]) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(2);
+        stream
+            .append(&compact(nibbles, true))
+            .append(&value.to_vec());
+        stream.out().to_vec()
+    }
+
+    // Children are RLP encoded nodes, they get embedded when shorter than 32 bytes and
+    // referenced by hash otherwise
+    fn branch(children: &[(usize, Vec<u8>)]) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(17);
+        for index in 0..16 {
+            match children.iter().find(|(i, _)| *i == index) {
+                Some((_, child)) if child.len() < 32 => {
+                    stream.append_raw(child, 1);
+                }
+                Some((_, child)) => {
+                    stream.append(&Keccak256::digest(child).to_vec());
+                }
+                None => {
+                    stream.append_empty_data();
+                }
+            }
+        }
+        stream.append_empty_data();
+        stream.out().to_vec()
+    }
+
+    fn extension(nibbles: &[u8], child: &[u8]) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&compact(nibbles, false));
+        if child.len() < 32 {
+            stream.append_raw(child, 1);
+        } else {
+            stream.append(&Keccak256::digest(child).to_vec());
+        }
+        stream.out().to_vec()
+    }
+
+    fn root_of(node: &[u8]) -> H256 {
+        H256::from_slice(&Keccak256::digest(node))
+    }
+
+    #[test]
+    fn test_inline_leaves_in_branch() {
+        // Small trie with two short keys, 0x10 => "a" and 0x20 => "b": both leaves are 3
+        // bytes long and embedded in the root branch
+        let leaf_a = leaf(&[0x0], b"a");
+        let leaf_b = leaf(&[0x0], b"b");
+        assert!(leaf_a.len() < 32);
+        let root = branch(&[(1, leaf_a), (2, leaf_b)]);
+        let proof = vec![root.clone()];
+
+        assert_eq!(
+            verify_proof(root_of(&root), &[0x10], &proof).unwrap(),
+            Some(b"a".to_vec())
+        );
+        assert_eq!(
+            verify_proof(root_of(&root), &[0x20], &proof).unwrap(),
+            Some(b"b".to_vec())
+        );
+        // Diverging inside the embedded leaf and at an empty branch slot
+        assert_eq!(verify_proof(root_of(&root), &[0x11], &proof).unwrap(), None);
+        assert_eq!(verify_proof(root_of(&root), &[0x30], &proof).unwrap(), None);
+    }
+
+    #[test]
+    fn test_inline_branch_behind_extension() {
+        // 0xabc1 => "x" and 0xabc2 => "y": an extension over "abc" pointing to an embedded
+        // branch with two embedded leaves
+        let inner = branch(&[(1, leaf(&[], b"x")), (2, leaf(&[], b"y"))]);
+        assert!(inner.len() < 32);
+        let root = extension(&[0xa, 0xb, 0xc], &inner);
+        let proof = vec![root.clone()];
+
+        assert_eq!(
+            verify_proof(root_of(&root), &[0xab, 0xc1], &proof).unwrap(),
+            Some(b"x".to_vec())
+        );
+        assert_eq!(
+            verify_proof(root_of(&root), &[0xab, 0xc2], &proof).unwrap(),
+            Some(b"y".to_vec())
+        );
+        assert_eq!(
+            verify_proof(root_of(&root), &[0xab, 0xc3], &proof).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_mixed_hashed_and_inline_nodes() {
+        // One child is large enough to be referenced by hash, the other one is embedded
+        let large_value = [0x42u8; 40];
+        let hashed_leaf = leaf(&[0x0, 0x0, 0x1], &large_value);
+        let inline_leaf = leaf(&[0x0, 0x0, 0x2], b"c");
+        let root = branch(&[(3, hashed_leaf.clone()), (4, inline_leaf)]);
+        let proof = vec![root.clone(), hashed_leaf];
+
+        assert_eq!(
+            verify_proof(root_of(&root), &[0x30, 0x01], &proof).unwrap(),
+            Some(large_value.to_vec())
+        );
+        assert_eq!(
+            verify_proof(root_of(&root), &[0x40, 0x02], &proof[..1]).unwrap(),
+            Some(b"c".to_vec())
+        );
+        // The hashed leaf has to be part of the proof
+        assert!(verify_proof(root_of(&root), &[0x30, 0x01], &proof[..1]).is_err());
+    }
+
+    #[test]
+    fn test_tampered_inline_node_is_rejected() {
+        let root = branch(&[(1, leaf(&[0x0], b"a")), (2, leaf(&[0x0], b"b"))]);
+        let tampered = branch(&[(1, leaf(&[0x0], b"z")), (2, leaf(&[0x0], b"b"))]);
+
+        assert!(verify_proof(root_of(&root), &[0x10], &[tampered]).is_err());
+    }
+
+    #[test]
+    fn test_exclusion_reports_divergence_node() {
+        let root = branch(&[(1, leaf(&[0x0], b"a")), (2, leaf(&[0x0], b"b"))]);
+        let proof = vec![root.clone()];
+
+        assert_eq!(
+            verify_trie_proof(root_of(&root), &[0x30], &proof),
+            ProofVerification::Exclusion(Divergence {
+                node: Some(root.clone()),
+                depth: 0,
+                reason: DivergenceReason::EmptyBranchChild { nibble: 3 },
+            })
+        );
+        assert_eq!(
+            verify_trie_proof(root_of(&root), &[0x11], &proof),
+            ProofVerification::Exclusion(Divergence {
+                node: Some(leaf(&[0x0], b"a")),
+                depth: 1,
+                reason: DivergenceReason::LeafPathMismatch,
+            })
+        );
+        assert_eq!(
+            verify_trie_proof(root_of(&root), &[0x10], &proof),
+            ProofVerification::Inclusion(b"a".to_vec())
+        );
+
+        let inner = branch(&[(1, leaf(&[], b"x")), (2, leaf(&[], b"y"))]);
+        let root = extension(&[0xa, 0xb, 0xc], &inner);
+        let proof = vec![root.clone()];
+        assert_eq!(
+            verify_trie_proof(root_of(&root), &[0xab, 0xd1], &proof),
+            ProofVerification::Exclusion(Divergence {
+                node: Some(root.clone()),
+                depth: 0,
+                reason: DivergenceReason::ExtensionPathMismatch,
+            })
+        );
+    }
+
+    #[test]
+    fn test_empty_trie_exclusion() {
+        let key = [0x12u8; 32];
+
+        // eth_getProof returns an empty proof for an account without storage
+        let result = verify_trie_proof(EMPTY_TRIE_ROOT, &key, &[]);
+        assert!(result.is_exclusion());
+        assert_eq!(
+            verify_trie_proof(EMPTY_TRIE_ROOT, &key, &[vec![0x80]]),
+            ProofVerification::Exclusion(Divergence {
+                node: Some(vec![0x80]),
+                depth: 0,
+                reason: DivergenceReason::EmptyTrie,
+            })
+        );
+        assert_eq!(
+            verify_storage_proof(EMPTY_TRIE_ROOT, &key, &[]).unwrap(),
+            None
+        );
+
+        // Any other root needs a proof
+        assert!(matches!(
+            verify_trie_proof(H256::repeat_byte(0x11), &key, &[]),
+            ProofVerification::Invalid(_)
+        ));
+    }
+
+    #[test]
+    fn test_unused_proof_nodes_are_rejected() {
+        let fixture = load_fixture();
+        let storage_proof = &fixture.storage_proofs[0];
+
+        let mut proof = storage_proof.proof.clone();
+        proof.push(fixture.storage_proofs[1].proof[0].clone());
+        assert_eq!(
+            verify_storage_proof(fixture.storage_root, &storage_proof.key, &proof).unwrap_err(),
+            ProofError::InvalidProof("Proof contains unused nodes".into())
+        );
+
+        // An exclusion proof must not carry extra nodes either
+        let root = branch(&[(1, leaf(&[0x0], b"a")), (2, leaf(&[0x0], b"b"))]);
+        let proof = vec![root.clone(), root.clone()];
+        assert!(matches!(
+            verify_trie_proof(root_of(&root), &[0x30], &proof),
+            ProofVerification::Invalid(_)
+        ));
+    }
+
+    #[test]
+    fn test_malformed_paths_are_invalid() {
+        // Even length leaf path (flag 0x2) with a non zero padding nibble
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&vec![0x25u8]).append(&b"a".to_vec());
+        let root = branch(&[(1, stream.out().to_vec())]);
+        let proof = vec![root.clone()];
+        assert!(matches!(
+            verify_trie_proof(root_of(&root), &[0x15], &proof),
+            ProofVerification::Invalid(ProofError::InvalidPath(_))
+        ));
+
+        // Extension without a shared path
+        let inner = branch(&[(1, leaf(&[], b"x")), (2, leaf(&[], b"y"))]);
+        let root = extension(&[], &inner);
+        let proof = vec![root.clone()];
+        assert!(matches!(
+            verify_trie_proof(root_of(&root), &[0x10], &proof),
+            ProofVerification::Invalid(ProofError::InvalidPath(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_helpers_reject_malformed_input() {
+        assert_eq!(decode_compact(&[0x1a, 0xbc]), Ok((1, vec![0xa, 0xb, 0xc])));
+        assert_eq!(decode_compact(&[0x20]), Ok((2, vec![])));
+        assert!(decode_compact(&[]).is_err());
+        assert!(decode_compact(&[0x40, 0x12]).is_err());
+        assert!(decode_compact(&[0x05]).is_err());
+
+        let nibbles = NibbleSlice::new(&[0xab]);
+        assert_eq!(nibbles.at(1), Some(0xb));
+        assert_eq!(nibbles.at(2), None);
+        assert!(NibbleSlice::new(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_proof_limits() {
+        // 70 branches, each consuming one nibble of a 40 byte key
+        let key = [0x11u8; 40];
+        let mut node = leaf(&[1; 10], &[0xaa; 40]);
+        let mut proof = vec![node.clone()];
+        for _ in 0..70 {
+            node = branch(&[(1, node)]);
+            proof.insert(0, node.clone());
+        }
+        let root = root_of(&proof[0]);
+
+        let depth_error = ProofError::InvalidProof("Proof exceeds the maximum depth".into());
+        assert_eq!(
+            verify_proof(root, &key, &proof).unwrap_err(),
+            depth_error.clone()
+        );
+        // Walking a node cache is limited as well
+        let cache = NodeCache::from_proofs([proof.as_slice()]);
+        assert_eq!(
+            cache.verify(root, &key),
+            ProofVerification::Invalid(depth_error)
+        );
+
+        let oversized = leaf(&[1; 64], &vec![0u8; MAX_NODE_SIZE]);
+        let proof = vec![oversized.clone()];
+        assert_eq!(
+            verify_proof(root_of(&oversized), &[0x11; 32], &proof).unwrap_err(),
+            ProofError::InvalidProof("Proof node exceeds the maximum size".into())
+        );
+    }
+
+    #[test]
+    fn test_mutated_proofs_are_rejected_without_panicking() {
+        let fixture = load_fixture();
+        let storage_proof = &fixture.storage_proofs[0];
+
+        for node_index in 0..storage_proof.proof.len() {
+            for byte_index in 0..storage_proof.proof[node_index].len() {
+                let mut proof = storage_proof.proof.clone();
+                proof[node_index][byte_index] ^= 0x01;
+                assert!(
+                    verify_storage_proof(fixture.storage_root, &storage_proof.key, &proof).is_err()
+                );
+
+                proof[node_index].truncate(byte_index);
+                assert!(
+                    verify_storage_proof(fixture.storage_root, &storage_proof.key, &proof).is_err()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,268 @@
+#[cfg(test)]
+mod tests {
+    use ethereum_types::{H256, U256};
+    use merkle_verifier_core::framed::{FrameReader, FrameWriter};
+    use merkle_verifier_core::keccak::keccak256;
+    use merkle_verifier_core::merkle_patricia::ProofError;
+    use merkle_verifier_core::price_feed::*;
+    use merkle_verifier_core::test_utils::{StateBuilder, CONTRACT_CODE};
+    use merkle_verifier_core::token::{verify_token_balance, TOKEN_PROFILES};
+
+    const AGGREGATOR: [u8; 20] = [0x77; 20];
+    const ROUND: u32 = 1234;
+    const PHASE: u16 = 6;
+    const UPDATED_AT: u64 = 1_750_000_000;
+
+    // The test aggregator has the placeholder code of test contracts
+    fn ocr1_code_hashes() -> Vec<H256> {
+        vec![keccak256(CONTRACT_CODE)]
+    }
+
+    fn feed(description: &str) -> &'static FeedProfile {
+        FEED_PROFILES
+            .iter()
+            .find(|feed| feed.description == description)
+            .unwrap()
+    }
+
+    fn slot(number: u64) -> [u8; 32] {
+        H256::from_low_u64_be(number).into()
+    }
+
+    fn transmission(answer: U256, timestamp: u64) -> U256 {
+        answer | (U256::from(timestamp) << 192)
+    }
+
+    // State with the proxy of `feed` pointing at `AGGREGATOR`, whose latest round holds
+    // `transmission`
+    fn feed_state(feed: &FeedProfile, transmission: U256) -> StateBuilder {
+        let phase = U256::from(PHASE) | (U256::from_big_endian(&AGGREGATOR) << 16);
+        // Config digest and epoch below the round, threshold 3 right below it
+        let hot_vars = U256::from(0xdead_beef_u64)
+            | (U256::from(0x01_0203_0405_u64) << 128)
+            | (U256::from(3) << 168)
+            | (U256::from(ROUND) << 176);
+
+        let mut state = StateBuilder::new();
+        state
+            .contract(feed.proxy)
+            .storage(feed.proxy, slot(PROXY_PHASE_SLOT), phase)
+            .contract(AGGREGATOR)
+            .storage(AGGREGATOR, slot(AGGREGATOR_HOT_VARS_SLOT), hot_vars)
+            .storage(AGGREGATOR, transmission_slot(ROUND), transmission);
+        state
+    }
+
+    fn price_proof(state: &StateBuilder, feed: &FeedProfile) -> PriceProof {
+        PriceProof {
+            proxy: state.bundle(feed.proxy, &proxy_slots()),
+            aggregator: state.bundle(AGGREGATOR, &aggregator_slots(ROUND)),
+        }
+    }
+
+    #[test]
+    fn test_latest_round() {
+        // 0.00042 ETH per USDC
+        let usdc = feed("USDC / ETH");
+        let answer = U256::from(420_000_000_000_000u64);
+        let state = feed_state(usdc, transmission(answer, UPDATED_AT));
+
+        let price = price_proof(&state, usdc)
+            .verify(state.state_root(), UPDATED_AT + 600, &ocr1_code_hashes())
+            .unwrap();
+        assert_eq!(price.feed, usdc.proxy);
+        assert_eq!(price.aggregator, AGGREGATOR);
+        assert_eq!(price.round_id, (6u128 << 64) | 1234);
+        assert_eq!(price.answer, answer);
+        assert_eq!(price.decimals, 18);
+        assert_eq!(price.updated_at, UPDATED_AT);
+
+        // 2500 USDC are worth 1.05 ETH
+        let token = TOKEN_PROFILES
+            .iter()
+            .find(|token| token.symbol == "USDC")
+            .unwrap();
+        let holder = [0x99; 20];
+        let mut state = state.clone();
+        state.storage(
+            token.address,
+            token.balance_slot(&holder).into(),
+            U256::from(2_500_000_000u64),
+        );
+        state.account(token.address, 1, U256::zero());
+        let bundle = state.bundle(token.address, &token.required_slots(&holder));
+        let balance = verify_token_balance(state.state_root(), &bundle, &holder, None).unwrap();
+        assert_eq!(
+            eth_value(&balance, &price),
+            Ok(U256::from(105) * U256::exp10(16))
+        );
+        assert_eq!(eth_feed_for(&token.address), Some(usdc));
+
+        // A USD price does not value collateral in ETH
+        let eth_usd = feed("ETH / USD");
+        let state = feed_state(
+            eth_usd,
+            transmission(U256::from(2_500u64) * U256::exp10(8), UPDATED_AT),
+        );
+        let price = price_proof(&state, eth_usd)
+            .verify(state.state_root(), UPDATED_AT, &ocr1_code_hashes())
+            .unwrap();
+        assert!(eth_value(&balance, &price).is_err());
+    }
+
+    #[test]
+    fn test_staleness() {
+        let dai = feed("DAI / ETH");
+        let state = feed_state(dai, transmission(U256::exp10(15), UPDATED_AT));
+        let proof = price_proof(&state, dai);
+
+        assert!(proof
+            .verify(
+                state.state_root(),
+                UPDATED_AT + dai.heartbeat,
+                &ocr1_code_hashes()
+            )
+            .is_ok());
+        assert!(proof
+            .verify(
+                state.state_root(),
+                UPDATED_AT + dai.heartbeat + 1,
+                &ocr1_code_hashes()
+            )
+            .is_err());
+        // Round from the future of the block
+        assert!(proof
+            .verify(state.state_root(), UPDATED_AT - 1, &ocr1_code_hashes())
+            .is_err());
+    }
+
+    #[test]
+    fn test_framed_price_proof() {
+        let steth = feed("STETH / ETH");
+        let state = feed_state(steth, transmission(U256::exp10(18), UPDATED_AT));
+        let proof = price_proof(&state, steth);
+
+        let mut writer = FrameWriter::new();
+        proof.write(&mut writer);
+        let data = writer.finish();
+
+        let mut reader = FrameReader::new(&data);
+        let decoded = PriceProof::read(&mut reader).unwrap();
+        reader.finish().unwrap();
+
+        assert_eq!(
+            decoded.verify(state.state_root(), UPDATED_AT, &ocr1_code_hashes()),
+            proof.verify(state.state_root(), UPDATED_AT, &ocr1_code_hashes())
+        );
+        assert!(PriceProof::read(&mut FrameReader::new(&data[..data.len() - 1])).is_err());
+    }
+
+    #[test]
+    fn test_invalid_price_proofs() {
+        let steth = feed("STETH / ETH");
+        let state = feed_state(steth, transmission(U256::exp10(18), UPDATED_AT));
+        let root = state.state_root();
+
+        // Aggregator that is not the current phase of the proxy
+        let mut other = state.clone();
+        other
+            .contract([0x78; 20])
+            .storage(
+                [0x78; 20],
+                slot(AGGREGATOR_HOT_VARS_SLOT),
+                U256::from(ROUND) << 176,
+            )
+            .storage(
+                [0x78; 20],
+                transmission_slot(ROUND),
+                transmission(U256::one(), UPDATED_AT),
+            );
+        let mut proof = price_proof(&other, steth);
+        proof.aggregator = other.bundle([0x78; 20], &aggregator_slots(ROUND));
+        assert!(proof
+            .verify(other.state_root(), UPDATED_AT, &ocr1_code_hashes())
+            .is_err());
+
+        // An older round than the latest one
+        let mut older = state.clone();
+        older.storage(
+            AGGREGATOR,
+            transmission_slot(ROUND - 1),
+            transmission(U256::one(), UPDATED_AT),
+        );
+        let mut proof = price_proof(&older, steth);
+        proof.aggregator = older.bundle(
+            AGGREGATOR,
+            &[slot(AGGREGATOR_HOT_VARS_SLOT), transmission_slot(ROUND - 1)],
+        );
+        assert!(proof
+            .verify(older.state_root(), UPDATED_AT, &ocr1_code_hashes())
+            .is_err());
+
+        // Phase slot not proven
+        let mut proof = price_proof(&state, steth);
+        proof.proxy = state.bundle(steth.proxy, &[slot(0)]);
+        assert!(proof.verify(root, UPDATED_AT, &ocr1_code_hashes()).is_err());
+
+        // Unknown feed
+        let mut proof = price_proof(&state, steth);
+        proof.proxy.address = [0x11; 20];
+        assert!(proof.verify(root, UPDATED_AT, &ocr1_code_hashes()).is_err());
+
+        // Aggregator with another layout
+        let mut other = state.clone();
+        other.code(AGGREGATOR, &[0x60, 0x80, 0x61]);
+        let proof = price_proof(&other, steth);
+        assert_eq!(
+            proof.verify(other.state_root(), UPDATED_AT, &ocr1_code_hashes()),
+            Err(ProofError::InvalidProof("Unknown aggregator layout".into()))
+        );
+        let proof = price_proof(&state, steth);
+        assert!(proof.verify(root, UPDATED_AT, &[]).is_err());
+
+        // Answers above the i128 range are proven like any other
+        let large = U256::from(u128::MAX) << 40;
+        let state = feed_state(steth, transmission(large, UPDATED_AT));
+        let price = price_proof(&state, steth)
+            .verify(state.state_root(), UPDATED_AT, &ocr1_code_hashes())
+            .unwrap();
+        assert_eq!(price.answer, large);
+
+        // Negative and zero answers (int192 in two's complement)
+        let negative = (U256::one() << 192) - U256::from(5);
+        for answer in [negative, U256::zero()] {
+            let state = feed_state(steth, transmission(answer, UPDATED_AT));
+            let proof = price_proof(&state, steth);
+            assert!(proof
+                .verify(state.state_root(), UPDATED_AT, &ocr1_code_hashes())
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_slot_decoding() {
+        let value = U256::from(3) | (U256::from_big_endian(&[0xab; 20]) << 16);
+        assert_eq!(decode_phase(value), Ok((3, [0xab; 20])));
+        assert_eq!(decode_latest_round(U256::from(77) << 176), Ok(77));
+        assert_eq!(
+            decode_transmission(transmission(U256::from(9), 5)),
+            Ok(((false, U256::from(9)), 5))
+        );
+
+        // The whole int192 range, beyond the range of an i128
+        let max = (U256::one() << 191) - 1;
+        assert_eq!(
+            decode_transmission(transmission(max, 5)),
+            Ok(((false, max), 5))
+        );
+        assert_eq!(
+            decode_transmission(transmission(U256::one() << 191, 5)),
+            Ok(((true, U256::one() << 191), 5))
+        );
+        assert_eq!(
+            decode_transmission(transmission((U256::one() << 192) - 1, 5)),
+            Ok(((true, U256::one()), 5))
+        );
+        assert_ne!(transmission_slot(1), transmission_slot(2));
+    }
+}
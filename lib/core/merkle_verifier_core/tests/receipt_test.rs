@@ -0,0 +1,229 @@
+#[cfg(test)]
+mod tests {
+    use ethereum_types::{Bloom, H160, H256};
+    use merkle_verifier_core::merkle_patricia::ProofError;
+    use merkle_verifier_core::receipt::*;
+    use rlp::RlpStream;
+    use sha3::{Digest, Keccak256};
+
+    const REPAID: &str = "Repaid(address,uint256)";
+    const LIQUIDATED: &str = "Liquidated(address,uint256)";
+
+    fn lending() -> H160 {
+        H160::repeat_byte(0xe7)
+    }
+
+    fn borrower_topic() -> H256 {
+        H256::from(H160::repeat_byte(0xf3))
+    }
+
+    fn log_rlp(stream: &mut RlpStream, address: H160, topics: &[H256], data: &[u8]) {
+        stream.begin_list(3);
+        stream.append(&address);
+        stream.append_list(topics);
+        stream.append(&data.to_vec());
+    }
+
+    // Receipt with one log per (emitter, event signature)
+    fn receipt(tx_type: u8, status: u8, logs: &[(H160, &str)]) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(4);
+        stream.append(&status);
+        stream.append(&21_000u64);
+        stream.append(&Bloom::zero());
+        stream.begin_list(logs.len());
+        for (address, signature) in logs {
+            log_rlp(
+                &mut stream,
+                *address,
+                &[event_topic(signature), borrower_topic()],
+                &[0x01; 32],
+            );
+        }
+
+        let mut encoded = Vec::new();
+        if tx_type != 0 {
+            encoded.push(tx_type);
+        }
+        encoded.extend_from_slice(&stream.out());
+        encoded
+    }
+
+    fn leaf(nibbles: &[u8], value: &[u8]) -> Vec<u8> {
+        // Hex prefix encoding, only used with at most one nibble here
+        let path = match nibbles {
+            [] => vec![0x20],
+            [nibble] => vec![0x30 | nibble],
+            _ => unreachable!(),
+        };
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&path).append(&value.to_vec());
+        stream.out().to_vec()
+    }
+
+    // Children are hashed, all nodes in these tests are at least 32 bytes long
+    fn branch(children: &[(usize, &Vec<u8>)]) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(17);
+        for index in 0..16 {
+            match children.iter().find(|(i, _)| *i == index) {
+                Some((_, child)) => stream.append(&Keccak256::digest(child).to_vec()),
+                None => stream.append_empty_data(),
+            };
+        }
+        stream.append_empty_data();
+        stream.out().to_vec()
+    }
+
+    struct ReceiptTrie {
+        root: H256,
+        proofs: Vec<ReceiptProof>,
+    }
+
+    // Block with three transactions: keys are rlp(0) = 0x80, rlp(1) = 0x01, rlp(2) = 0x02
+    fn receipt_trie(receipts: [Vec<u8>; 3]) -> ReceiptTrie {
+        let leaf_0 = leaf(&[0x0], &receipts[0]);
+        let leaf_1 = leaf(&[], &receipts[1]);
+        let leaf_2 = leaf(&[], &receipts[2]);
+        let inner = branch(&[(1, &leaf_1), (2, &leaf_2)]);
+        let root = branch(&[(0, &inner), (8, &leaf_0)]);
+
+        ReceiptTrie {
+            root: H256::from_slice(&Keccak256::digest(&root)),
+            proofs: vec![
+                ReceiptProof {
+                    tx_index: 0,
+                    proof: vec![root.clone(), leaf_0],
+                },
+                ReceiptProof {
+                    tx_index: 1,
+                    proof: vec![root.clone(), inner.clone(), leaf_1],
+                },
+                ReceiptProof {
+                    tx_index: 2,
+                    proof: vec![root, inner, leaf_2],
+                },
+            ],
+        }
+    }
+
+    fn sample_trie() -> ReceiptTrie {
+        receipt_trie([
+            // legacy
+            receipt(0, 1, &[(lending(), REPAID)]),
+            // EIP-1559, an unrelated contract emitting the same event next to a liquidation
+            receipt(
+                2,
+                1,
+                &[(H160::repeat_byte(0x11), REPAID), (lending(), LIQUIDATED)],
+            ),
+            // EIP-4844, reverted
+            receipt(3, 0, &[]),
+        ])
+    }
+
+    #[test]
+    fn test_verify_typed_and_legacy_receipts() {
+        let trie = sample_trie();
+
+        let legacy = verify_receipt_proof(trie.root, 0, &trie.proofs[0].proof)
+            .unwrap()
+            .unwrap();
+        assert_eq!(legacy.tx_type, 0);
+        assert!(legacy.success);
+        assert_eq!(legacy.logs.len(), 1);
+        assert_eq!(legacy.logs[0].address, lending());
+        assert_eq!(legacy.logs[0].topics[1], borrower_topic());
+
+        let eip1559 = verify_receipt_proof(trie.root, 1, &trie.proofs[1].proof)
+            .unwrap()
+            .unwrap();
+        assert_eq!(eip1559.tx_type, 2);
+        assert_eq!(eip1559.logs.len(), 2);
+
+        let reverted = verify_receipt_proof(trie.root, 2, &trie.proofs[2].proof)
+            .unwrap()
+            .unwrap();
+        assert_eq!(reverted.tx_type, 3);
+        assert!(!reverted.success);
+    }
+
+    #[test]
+    fn test_count_proven_logs_by_emitter_and_topic() {
+        let trie = sample_trie();
+
+        let repaid = LogFilter::event(lending(), REPAID);
+        let liquidated = LogFilter::event(lending(), LIQUIDATED);
+        assert_eq!(
+            count_proven_logs(trie.root, &trie.proofs, &repaid).unwrap(),
+            1
+        );
+        assert_eq!(
+            count_proven_logs(trie.root, &trie.proofs, &liquidated).unwrap(),
+            1
+        );
+
+        // Any emitter, filtered by event and borrower
+        let any_repaid_by_borrower = LogFilter {
+            address: None,
+            topics: vec![Some(event_topic(REPAID)), Some(borrower_topic())],
+        };
+        assert_eq!(
+            count_proven_logs(trie.root, &trie.proofs, &any_repaid_by_borrower).unwrap(),
+            2
+        );
+
+        // Other borrower
+        let other_borrower = LogFilter {
+            address: Some(lending()),
+            topics: vec![None, Some(H256::repeat_byte(0x99))],
+        };
+        assert_eq!(
+            count_proven_logs(trie.root, &trie.proofs, &other_borrower).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_count_proven_logs_rejects_invalid_proofs() {
+        let trie = sample_trie();
+        let repaid = LogFilter::event(lending(), REPAID);
+
+        // The same receipt twice
+        let duplicated = vec![trie.proofs[0].clone(), trie.proofs[0].clone()];
+        assert!(count_proven_logs(trie.root, &duplicated, &repaid).is_err());
+
+        // Proof of another transaction
+        let mismatched = vec![ReceiptProof {
+            tx_index: 0,
+            proof: trie.proofs[1].proof.clone(),
+        }];
+        assert!(count_proven_logs(trie.root, &mismatched, &repaid).is_err());
+
+        // A receipt that does not exist can not be counted
+        let missing = vec![ReceiptProof {
+            tx_index: 3,
+            proof: trie.proofs[2].proof[..2].to_vec(),
+        }];
+        assert!(count_proven_logs(trie.root, &missing, &repaid).is_err());
+    }
+
+    #[test]
+    fn test_decode_receipt_rejects_malformed_input() {
+        assert!(decode_receipt(&[]).is_err());
+
+        let mut encoded = receipt(2, 1, &[]);
+        encoded.push(0x00);
+        assert!(decode_receipt(&encoded).is_err());
+
+        // Legacy receipt prefixed with type 0
+        let mut prefixed = vec![0x00];
+        prefixed.extend_from_slice(&receipt(0, 1, &[]));
+        assert!(decode_receipt(&receipt(0, 1, &[])).is_ok());
+        assert_eq!(
+            decode_receipt(&prefixed),
+            Err(ProofError::RlpDecoding("Unsupported receipt type".into()))
+        );
+
+        // Status other than 0, 1 or a state root
+        assert!(decode_receipt(&receipt(2, 2, &[])).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+#[cfg(test)]
+mod tests {
+    use ethereum_types::U256;
+    use merkle_verifier_core::framed::{FrameReader, FrameWriter};
+    use merkle_verifier_core::keccak::keccak256;
+    use merkle_verifier_core::staking::*;
+    use merkle_verifier_core::test_utils::StateBuilder;
+    use merkle_verifier_core::token::token_profile;
+
+    const HOLDER: [u8; 20] = [0x99; 20];
+
+    fn ether(amount: u64) -> U256 {
+        U256::from(amount) * U256::exp10(18)
+    }
+
+    fn position(name: &str) -> [u8; 32] {
+        keccak256(name.as_bytes()).into()
+    }
+
+    fn balance_slot(token: StakingToken) -> [u8; 32] {
+        token_profile(&token.address())
+            .unwrap()
+            .balance_slot(&HOLDER)
+            .into()
+    }
+
+    // Lido pools 100 ETH buffered, 9000 ETH on the beacon chain and 10 pending deposits:
+    // 9420 ETH for 7850 shares, 1.2 ETH per share. Rocket Pool holds 1100 ETH for 1000 rETH.
+    fn staking_state() -> StateBuilder {
+        let (rocket_total, rocket_supply) = match StakingToken::REth.rate_slots()[..] {
+            [total, supply] => (total, supply),
+            _ => unreachable!(),
+        };
+
+        let mut state = StateBuilder::new();
+        state
+            .contract(LIDO)
+            .storage(LIDO, position("lido.Lido.bufferedEther"), ether(100))
+            .storage(LIDO, position("lido.Lido.beaconBalance"), ether(9000))
+            .storage(
+                LIDO,
+                position("lido.Lido.depositedValidators"),
+                U256::from(300),
+            )
+            .storage(
+                LIDO,
+                position("lido.Lido.beaconValidators"),
+                U256::from(290),
+            )
+            .storage(LIDO, position("lido.StETH.totalShares"), ether(7850))
+            .storage(LIDO, balance_slot(StakingToken::StEth), ether(10))
+            .contract(WSTETH)
+            .storage(WSTETH, balance_slot(StakingToken::WstEth), ether(5))
+            .contract(ROCKET_STORAGE)
+            .storage(ROCKET_STORAGE, rocket_total, ether(1100))
+            .storage(ROCKET_STORAGE, rocket_supply, ether(1000))
+            .contract(RETH)
+            .storage(RETH, balance_slot(StakingToken::REth), ether(2));
+        state
+    }
+
+    fn staking_proof(state: &StateBuilder, token: StakingToken) -> StakingProof {
+        let (token_slots, rate_slots) = token.required_slots(&HOLDER);
+        StakingProof {
+            token: state.bundle(token.address(), &token_slots),
+            rate: (!rate_slots.is_empty())
+                .then(|| state.bundle(token.rate_contract(), &rate_slots)),
+        }
+    }
+
+    #[test]
+    fn test_eth_equivalents() {
+        let state = staking_state();
+        let root = state.state_root();
+
+        let steth = staking_proof(&state, StakingToken::StEth)
+            .verify(root, &HOLDER)
+            .unwrap();
+        assert_eq!(steth.token, LIDO);
+        assert_eq!(steth.amount, ether(12));
+        assert_eq!(steth.eth, ether(12));
+
+        let wsteth = staking_proof(&state, StakingToken::WstEth)
+            .verify(root, &HOLDER)
+            .unwrap();
+        assert_eq!(wsteth.amount, ether(5));
+        assert_eq!(wsteth.eth, ether(6));
+
+        let reth = staking_proof(&state, StakingToken::REth)
+            .verify(root, &HOLDER)
+            .unwrap();
+        assert_eq!(reth.amount, ether(2));
+        assert_eq!(reth.eth, U256::from(22) * U256::exp10(17));
+
+        assert_eq!(
+            total_eth_equivalent(&[steth, wsteth, reth]),
+            Ok(20_200_000_000_000_000_000)
+        );
+        assert_eq!(total_eth_equivalent(&[]), Ok(0));
+
+        // Sums that do not fit the u128 balance of the score input
+        let huge = StakedBalance {
+            eth: U256::from(u128::MAX),
+            ..steth
+        };
+        assert!(total_eth_equivalent(&[huge, steth]).is_err());
+        let huge = StakedBalance {
+            eth: U256::from(u128::MAX) + 1,
+            ..steth
+        };
+        assert!(total_eth_equivalent(&[huge]).is_err());
+    }
+
+    #[test]
+    fn test_holder_without_balance() {
+        let state = staking_state();
+        let proof = staking_proof(&state, StakingToken::WstEth);
+        let mut other = proof.clone();
+        other.token = state.bundle(
+            WSTETH,
+            &token_profile(&WSTETH).unwrap().required_slots(&[0x42; 20]),
+        );
+
+        let balance = other.verify(state.state_root(), &[0x42; 20]).unwrap();
+        assert_eq!(balance.eth, U256::zero());
+    }
+
+    #[test]
+    fn test_framed_staking_proofs() {
+        let state = staking_state();
+        let root = state.state_root();
+
+        // stETH comes without a rate bundle, rETH with one
+        for token in [StakingToken::StEth, StakingToken::REth] {
+            let proof = staking_proof(&state, token);
+            let mut writer = FrameWriter::new();
+            proof.write(&mut writer);
+            let data = writer.finish();
+
+            let mut reader = FrameReader::new(&data);
+            let decoded = StakingProof::read(&mut reader).unwrap();
+            reader.finish().unwrap();
+            assert_eq!(decoded.rate.is_some(), proof.rate.is_some());
+            assert_eq!(decoded.verify(root, &HOLDER), proof.verify(root, &HOLDER));
+        }
+
+        let mut writer = FrameWriter::new();
+        staking_proof(&state, StakingToken::StEth).write(&mut writer);
+        let mut data = writer.finish();
+        *data.last_mut().unwrap() = 2;
+        assert!(StakingProof::read(&mut FrameReader::new(&data)).is_err());
+    }
+
+    #[test]
+    fn test_invalid_staking_proofs() {
+        let state = staking_state();
+        let root = state.state_root();
+
+        // Rate of the other protocol
+        let mut proof = staking_proof(&state, StakingToken::REth);
+        proof.rate = staking_proof(&state, StakingToken::WstEth).rate;
+        assert!(proof.verify(root, &HOLDER).is_err());
+
+        // Missing rate, and a separate rate for stETH
+        let mut proof = staking_proof(&state, StakingToken::WstEth);
+        proof.rate = None;
+        assert!(proof.verify(root, &HOLDER).is_err());
+        let mut proof = staking_proof(&state, StakingToken::StEth);
+        proof.rate = Some(proof.token.clone());
+        assert!(proof.verify(root, &HOLDER).is_err());
+
+        // Rate slot not proven
+        let mut proof = staking_proof(&state, StakingToken::REth);
+        proof.rate = Some(state.bundle(ROCKET_STORAGE, &StakingToken::REth.rate_slots()[..1]));
+        assert!(proof.verify(root, &HOLDER).is_err());
+
+        // Not a liquid staking token
+        let mut proof = staking_proof(&state, StakingToken::REth);
+        proof.token.address = [0x11; 20];
+        assert!(proof.verify(root, &HOLDER).is_err());
+
+        // More validators on the beacon chain than deposited
+        let mut broken = state.clone();
+        broken.storage(
+            LIDO,
+            position("lido.Lido.beaconValidators"),
+            U256::from(301),
+        );
+        let proof = staking_proof(&broken, StakingToken::StEth);
+        assert!(proof.verify(broken.state_root(), &HOLDER).is_err());
+    }
+
+    #[test]
+    fn test_rocket_pool_without_supply() {
+        let mut state = staking_state();
+        state.storage(
+            ROCKET_STORAGE,
+            StakingToken::REth.rate_slots()[1],
+            U256::zero(),
+        );
+
+        let balance = staking_proof(&state, StakingToken::REth)
+            .verify(state.state_root(), &HOLDER)
+            .unwrap();
+        assert_eq!(balance.eth, ether(2));
+    }
+}
@@ -0,0 +1,175 @@
+#[cfg(test)]
+mod tests {
+    use ethereum_types::{H160, H256, U256};
+    use merkle_verifier_core::storage_layout::*;
+    use sha3::{Digest, Keccak256};
+
+    fn h256(hex_str: &str) -> H256 {
+        H256::from_slice(&hex::decode(hex_str.trim_start_matches("0x")).unwrap())
+    }
+
+    fn user() -> [u8; 20] {
+        hex::decode("f39fd6e51aad88f6f4ce6ab8827279cfffb92266")
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_user_history_slots_match_fixture() {
+        // `mapping(address => UserHistory)` at slot 0 of the Lending contract, the slots
+        // requested in test_data/user_history_merkle_proof
+        let base = mapping_slot(&address_key(&user()), H256::zero());
+        assert_eq!(
+            base,
+            h256("723077b8a1b173adc35e5f0e7e3662fd1208212cb629f9c128551ea7168da722")
+        );
+        assert_eq!(
+            slot_offset(base, U256::from(2)),
+            h256("723077b8a1b173adc35e5f0e7e3662fd1208212cb629f9c128551ea7168da724")
+        );
+    }
+
+    #[test]
+    fn test_nested_mapping_and_bytes_keys() {
+        // allowance[owner][spender] at slot 1
+        let owner = address_key(&user());
+        let spender = address_key(&[0x11; 20]);
+        let slot = H256::from_low_u64_be(1);
+        assert_eq!(
+            nested_mapping_slot(&[owner, spender], slot),
+            mapping_slot(&spender, mapping_slot(&owner, slot))
+        );
+        assert_eq!(nested_mapping_slot(&[], slot), slot);
+
+        // string keys are hashed unpadded
+        let mut data = b"USDC".to_vec();
+        data.extend_from_slice(slot.as_bytes());
+        assert_eq!(
+            mapping_slot_for_bytes_key(b"USDC", slot),
+            H256::from_slice(&Keccak256::digest(&data))
+        );
+    }
+
+    #[test]
+    fn test_array_slots() {
+        let slot = H256::from_low_u64_be(3);
+        let data_slot = H256::from_slice(&Keccak256::digest(slot.as_bytes()));
+
+        assert_eq!(array_length(U256::from(5)).unwrap(), 5);
+        assert!(array_length(U256::MAX).is_err());
+
+        // struct elements of two slots each
+        assert_eq!(array_element_slot(slot, 0, 2), data_slot);
+        assert_eq!(
+            array_element_slot(slot, 3, 2),
+            slot_offset(data_slot, U256::from(6))
+        );
+
+        // uint64[]: four elements per slot
+        assert_eq!(
+            packed_array_element(slot, 5, 8).unwrap(),
+            (slot_offset(data_slot, U256::one()), 8)
+        );
+        assert!(packed_array_element(slot, 5, 0).is_err());
+    }
+
+    #[test]
+    fn test_packed_members() {
+        // struct { address owner; uint64 since; bool active; } packed into one slot:
+        // owner at offset 0, since at offset 20, active at offset 28
+        let owner = U256::from_big_endian(&user());
+        let since = U256::from(1_700_000_000u64) << (20 * 8);
+        let active = U256::one() << (28 * 8);
+        let value = owner | since | active;
+
+        assert_eq!(
+            decode_address(extract_packed(value, 0, 20).unwrap()).unwrap(),
+            H160::from(user())
+        );
+        assert_eq!(
+            extract_packed(value, 20, 8).unwrap(),
+            U256::from(1_700_000_000u64)
+        );
+        assert!(decode_bool(extract_packed(value, 28, 1).unwrap()).unwrap());
+
+        assert!(extract_packed(value, 30, 4).is_err());
+        assert!(decode_address(value).is_err());
+        assert!(decode_bool(U256::from(2)).is_err());
+        assert!(decode_uint(U256::from(256), 1).is_err());
+    }
+
+    #[test]
+    fn test_signed_integers() {
+        assert_eq!(decode_int(U256::from(0xff), 1).unwrap(), -1);
+        assert_eq!(decode_int(U256::from(0x7f), 1).unwrap(), 127);
+        assert_eq!(decode_int(U256::from(0x80), 1).unwrap(), -128);
+        assert_eq!(decode_int(U256::MAX, 32).unwrap(), -1);
+        assert_eq!(decode_int(U256::one() << 127, 16).unwrap(), i128::MIN);
+
+        // int256 values beyond the i128 range
+        assert!(decode_int(U256::one() << 255, 32).is_err());
+        assert!(decode_int(U256::one() << 200, 32).is_err());
+        // not sign extended packed member
+        assert!(decode_int(U256::from(0x1ff), 1).is_err());
+
+        // Sign and magnitude of any width
+        assert_eq!(decode_signed(U256::from(0xff), 1), Ok((true, U256::one())));
+        assert_eq!(
+            decode_signed(U256::one() << 255, 32),
+            Ok((true, U256::one() << 255))
+        );
+        assert_eq!(
+            decode_signed(U256::one() << 200, 32),
+            Ok((false, U256::one() << 200))
+        );
+        assert!(decode_signed(U256::from(0x1ff), 1).is_err());
+    }
+
+    #[test]
+    fn test_bytes_and_strings() {
+        let slot = H256::from_low_u64_be(4);
+
+        // "USDC": left aligned, 2 * length in the lowest byte
+        let mut short = [0u8; 32];
+        short[..4].copy_from_slice(b"USDC");
+        short[31] = 8;
+        let value = U256::from_big_endian(&short);
+        assert_eq!(
+            decode_bytes_slot(slot, value).unwrap(),
+            BytesValue::Short(b"USDC".to_vec())
+        );
+        assert_eq!(decode_short_string(slot, value).unwrap(), "USDC");
+
+        // 40 byte value: 2 * 40 + 1 in the slot, data at keccak256(slot)
+        let data_slot = H256::from_slice(&Keccak256::digest(slot.as_bytes()));
+        let long = decode_bytes_slot(slot, U256::from(81)).unwrap();
+        assert_eq!(
+            long,
+            BytesValue::Long {
+                length: 40,
+                data_slot
+            }
+        );
+        assert!(decode_short_string(slot, U256::from(81)).is_err());
+
+        let slots = long_bytes_slots(data_slot, 40).unwrap();
+        assert_eq!(slots, vec![data_slot, slot_offset(data_slot, U256::one())]);
+        assert_eq!(
+            long_bytes_slots(data_slot, MAX_LONG_BYTES_LENGTH)
+                .unwrap()
+                .len(),
+            128
+        );
+        // Lengths taken from a hostile slot
+        assert!(long_bytes_slots(data_slot, MAX_LONG_BYTES_LENGTH + 1).is_err());
+        assert!(long_bytes_slots(data_slot, u64::MAX).is_err());
+        let values = [U256::MAX, U256::MAX];
+        assert_eq!(assemble_long_bytes(&values, 40).unwrap(), vec![0xff; 40]);
+        assert!(assemble_long_bytes(&values[..1], 40).is_err());
+
+        // Garbage after the short value
+        short[10] = 1;
+        assert!(decode_bytes_slot(slot, U256::from_big_endian(&short)).is_err());
+    }
+}
@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod tests {
+    use ethereum_types::{H256, U256};
+    use merkle_verifier_core::storage_layout::{address_key, mapping_slot};
+    use merkle_verifier_core::test_utils::StateBuilder;
+    use merkle_verifier_core::token::*;
+
+    fn token(symbol: &str) -> &'static TokenProfile {
+        TOKEN_PROFILES
+            .iter()
+            .find(|profile| profile.symbol == symbol)
+            .unwrap()
+    }
+
+    fn holder() -> [u8; 20] {
+        hex::decode("28c6c06298d514db089934071355e5743bf21d60")
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    // State with the token contract holding `value` in the balance slot of the holder
+    fn token_state(profile: &TokenProfile, value: U256) -> StateBuilder {
+        let slot = profile.balance_slot(&holder()).into();
+        let mut state = StateBuilder::new();
+        state
+            .contract(profile.address)
+            .storage(profile.address, slot, value);
+        state
+    }
+
+    #[test]
+    fn test_registry() {
+        let usdc = token("USDC");
+        assert_eq!(token_profile(&usdc.address), Some(usdc));
+        assert_eq!(
+            hex::encode(usdc.address),
+            "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+        );
+        assert_eq!(token_profile(&[0; 20]), None);
+
+        // Same slot the cycles benchmark requests for the USDC holder
+        assert_eq!(
+            usdc.balance_slot(&holder()),
+            mapping_slot(&address_key(&holder()), H256::from_low_u64_be(9))
+        );
+        assert_eq!(
+            usdc.required_slots(&holder()),
+            vec![<[u8; 32]>::from(usdc.balance_slot(&holder()))]
+        );
+    }
+
+    #[test]
+    fn test_balance_proofs() {
+        // 1234.5 USDC of a blacklisted holder: the flag is not part of the balance
+        let usdc = token("USDC");
+        let blacklisted = (U256::one() << 255) | U256::from(1_234_500_000u64);
+        let state = token_state(usdc, blacklisted);
+        let bundle = state.bundle(usdc.address, &usdc.required_slots(&holder()));
+
+        let balance = verify_token_balance(state.state_root(), &bundle, &holder(), None).unwrap();
+        assert_eq!(balance.amount, U256::from(1_234_500_000u64));
+        assert_eq!(balance.decimals, 6);
+        assert_eq!(
+            balance.normalized,
+            U256::from(1_234_500_000u64) * U256::exp10(12)
+        );
+
+        // Holders without a balance have no slot in the trie
+        let other = [0x42; 20];
+        let bundle = state.bundle(usdc.address, &usdc.required_slots(&other));
+        let balance = verify_token_balance(state.state_root(), &bundle, &other, None).unwrap();
+        assert_eq!(balance.amount, U256::zero());
+
+        // Against the storage root of the token directly
+        let wbtc = token("WBTC");
+        let state = token_state(wbtc, U256::from(150_000_000u64));
+        let slots = wbtc.required_slots(&holder());
+        let storage_proofs = state.bundle(wbtc.address, &slots).storage_proofs;
+        let balance = wbtc
+            .verify_balance(
+                state.storage_root(wbtc.address),
+                &holder(),
+                &storage_proofs,
+                None,
+            )
+            .unwrap();
+        assert_eq!(balance.normalized, U256::from(15u64) * U256::exp10(17));
+    }
+
+    #[test]
+    fn test_rebasing_shares() {
+        let steth = token("stETH");
+        let state = token_state(steth, U256::exp10(18));
+        let bundle = state.bundle(steth.address, &steth.required_slots(&holder()));
+
+        // The balance of shares needs the rate of the pool
+        assert!(verify_token_balance(state.state_root(), &bundle, &holder(), None).is_err());
+
+        let rate = ShareRate {
+            pooled: U256::from(115) * U256::exp10(16),
+            shares: U256::exp10(18),
+        };
+        let balance =
+            verify_token_balance(state.state_root(), &bundle, &holder(), Some(&rate)).unwrap();
+        assert_eq!(balance.amount, U256::from(115) * U256::exp10(16));
+
+        let empty_pool = ShareRate {
+            pooled: U256::zero(),
+            shares: U256::zero(),
+        };
+        assert!(
+            verify_token_balance(state.state_root(), &bundle, &holder(), Some(&empty_pool))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_invalid_balance_proofs() {
+        let dai = token("DAI");
+        let mut state = token_state(dai, U256::from(5));
+
+        // Proof of another slot
+        state.storage(dai.address, [0xab; 32], U256::from(7));
+        let bundle = state.bundle(dai.address, &[[0xab; 32]]);
+        assert!(verify_token_balance(state.state_root(), &bundle, &holder(), None).is_err());
+
+        // Token that is not registered
+        let mut unknown = state.bundle(dai.address, &dai.required_slots(&holder()));
+        unknown.address = [0x11; 20];
+        assert!(verify_token_balance(state.state_root(), &unknown, &holder(), None).is_err());
+
+        // Registered token missing from the state
+        let weth = token("WETH");
+        let bundle = state.bundle(weth.address, &weth.required_slots(&holder()));
+        assert!(verify_token_balance(state.state_root(), &bundle, &holder(), None).is_err());
+
+        // Wrong state root
+        let bundle = state.bundle(dai.address, &dai.required_slots(&holder()));
+        let root = state.storage_root(dai.address);
+        assert!(verify_token_balance(root, &bundle, &holder(), None).is_err());
+    }
+
+    #[test]
+    fn test_normalize_amount() {
+        assert_eq!(normalize_amount(U256::from(1), 6), Ok(U256::exp10(12)));
+        assert_eq!(normalize_amount(U256::from(7), 18), Ok(U256::from(7)));
+        // More decimals than normalized are rounded down
+        assert_eq!(normalize_amount(U256::from(1999), 21), Ok(U256::from(1)));
+        assert!(normalize_amount(U256::MAX, 6).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use merkle_verifier_core::merkle_patricia::{encode_path, verify_proof, ProofError};
+    use merkle_verifier_core::receipt::tx_index_key;
+    use merkle_verifier_core::test_utils::MemoryTrie;
+    use merkle_verifier_core::trace::*;
+
+    fn sample_trie() -> MemoryTrie {
+        let mut trie = MemoryTrie::new();
+        for index in 0..40u64 {
+            trie.insert(&tx_index_key(index), vec![index as u8 + 1; 40]);
+        }
+        trie
+    }
+
+    #[test]
+    fn test_trace_of_valid_proof() {
+        let trie = sample_trie();
+        let key = tx_index_key(17);
+        let proof = trie.proof(&key);
+
+        let trace = trace_proof(trie.root(), &key, &proof);
+        assert!(trace.verification.is_inclusion());
+        assert_eq!(trace.proof_len, proof.len());
+
+        // The steps consume the whole key and every proof node matches its reference
+        let consumed: Vec<u8> = trace
+            .steps
+            .iter()
+            .flat_map(|step| step.nibbles.clone())
+            .collect();
+        assert_eq!(consumed, encode_path(&key));
+        assert_eq!(trace.steps.last().unwrap().kind, Some(NodeKind::Leaf));
+        for step in &trace.steps {
+            assert!(step.proof_index.is_some());
+            assert_eq!(step.expected_hash, step.actual_hash);
+        }
+
+        assert_eq!(
+            verify_proof_traced(trie.root(), &key, &proof).unwrap(),
+            verify_proof(trie.root(), &key, &proof).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_trace_points_at_tampered_node() {
+        let trie = sample_trie();
+        let key = tx_index_key(17);
+        let mut proof = trie.proof(&key);
+        let last = proof.len() - 1;
+        proof[last][5] ^= 1;
+
+        let err = verify_proof_traced(trie.root(), &key, &proof).unwrap_err();
+        assert!(matches!(err.error, ProofError::HashMismatch(_)));
+
+        let failed = err.steps.last().unwrap();
+        assert_eq!(failed.proof_index, Some(last));
+        assert_eq!(failed.depth, err.steps[last - 1].depth + 1);
+        assert!(failed.expected_hash.is_some());
+        assert_ne!(failed.expected_hash, failed.actual_hash);
+
+        let printed = err.to_string();
+        assert!(printed.contains("HashMismatch"));
+        assert!(printed.contains(&format!("expected {:?}", failed.expected_hash.unwrap())));
+    }
+
+    #[test]
+    fn test_trace_of_truncated_proof() {
+        let trie = sample_trie();
+        let key = tx_index_key(17);
+        let mut proof = trie.proof(&key);
+        proof.pop();
+
+        let trace = trace_proof(trie.root(), &key, &proof);
+        assert_eq!(
+            trace.verification.into_result(),
+            Err(ProofError::InvalidProof("Proof too short".into()))
+        );
+        let missing = trace.steps.last().unwrap();
+        assert_eq!(missing.proof_index, Some(proof.len()));
+        assert_eq!(missing.actual_hash, None);
+        assert_eq!(missing.kind, None);
+    }
+
+    #[test]
+    fn test_trace_display() {
+        let mut trie = MemoryTrie::new();
+        trie.insert(b"do", b"verb".to_vec());
+        trie.insert(b"dog", b"puppy".to_vec());
+        trie.insert(b"horse", b"stallion".to_vec());
+
+        let trace = trace_proof(trie.root(), b"dog", &trie.proof(b"dog"));
+        let printed = trace.to_string();
+        assert!(printed.starts_with("Proof of key 0x646f67"));
+        assert!(printed.contains("embedded"));
+        assert!(printed.ends_with("Inclusion, value of 5 bytes"));
+
+        let trace = trace_proof(trie.root(), b"cat", &trie.proof(b"cat"));
+        assert!(trace
+            .to_string()
+            .ends_with("Exclusion at depth 1: EmptyBranchChild { nibble: 3 }"));
+    }
+}
@@ -0,0 +1,213 @@
+#[cfg(test)]
+mod tests {
+    use ethereum_types::{H160, H256, U256};
+    use k256::ecdsa::SigningKey;
+    use merkle_verifier_core::transaction::*;
+    use rlp::RlpStream;
+    use sha3::{Digest, Keccak256};
+
+    // Example from EIP-155: private key 0x4646...46, chain id 1
+    const EIP155_TX: &str = "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+
+    fn signer() -> (SigningKey, H160) {
+        let key = SigningKey::from_bytes(&[0x46u8; 32].into()).unwrap();
+        let sender =
+            H160::from_slice(&hex::decode("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f").unwrap());
+        (key, sender)
+    }
+
+    fn lending() -> H160 {
+        H160::repeat_byte(0xe7)
+    }
+
+    // `repay(uint256)` call data
+    fn repay_call() -> Vec<u8> {
+        let mut input = Keccak256::digest(b"repay(uint256)")[..4].to_vec();
+        input.extend_from_slice(&[0x01; 32]);
+        input
+    }
+
+    // Sign a typed transaction whose unsigned fields have been appended to `fields`
+    fn sign_typed(tx_type: u8, fields: &[Vec<u8>]) -> Vec<u8> {
+        let mut unsigned = RlpStream::new_list(fields.len());
+        for field in fields {
+            unsigned.append_raw(field, 1);
+        }
+        let mut payload = vec![tx_type];
+        payload.extend_from_slice(&unsigned.out());
+
+        let (signature, recovery_id) = signer()
+            .0
+            .sign_prehash_recoverable(&Keccak256::digest(&payload))
+            .unwrap();
+
+        let mut signed = RlpStream::new_list(fields.len() + 3);
+        for field in fields {
+            signed.append_raw(field, 1);
+        }
+        signed.append(&(recovery_id.is_y_odd() as u8));
+        signed.append(&U256::from_big_endian(&signature.r().to_bytes()));
+        signed.append(&U256::from_big_endian(&signature.s().to_bytes()));
+
+        let mut encoded = vec![tx_type];
+        encoded.extend_from_slice(&signed.out());
+        encoded
+    }
+
+    fn item<T: rlp::Encodable>(value: &T) -> Vec<u8> {
+        rlp::encode(value).to_vec()
+    }
+
+    fn empty_list() -> Vec<u8> {
+        vec![0xc0]
+    }
+
+    // [chainId, nonce, (fees), gasLimit, to, value, data, accessList, (blob fields)]
+    fn typed_fields(tx_type: u8) -> Vec<Vec<u8>> {
+        let mut fields = vec![item(&1u64), item(&7u64)];
+        match tx_type {
+            ACCESS_LIST_TX_TYPE => fields.push(item(&20_000_000_000u64)),
+            _ => {
+                fields.push(item(&1_000_000_000u64));
+                fields.push(item(&30_000_000_000u64));
+            }
+        }
+        fields.push(item(&100_000u64));
+        fields.push(item(&lending()));
+        fields.push(item(&U256::from(5u64)));
+        fields.push(item(&repay_call()));
+        fields.push(empty_list());
+        if tx_type == BLOB_TX_TYPE {
+            fields.push(item(&1u64));
+            let mut hashes = RlpStream::new_list(1);
+            hashes.append(&H256::repeat_byte(0x01));
+            fields.push(hashes.out().to_vec());
+        }
+        fields
+    }
+
+    #[test]
+    fn test_recover_sender_of_eip155_transaction() {
+        let encoded = hex::decode(EIP155_TX).unwrap();
+
+        let transaction = verify_transaction(&encoded).unwrap();
+        assert_eq!(transaction.tx_type, LEGACY_TX_TYPE);
+        assert_eq!(transaction.chain_id, Some(1));
+        assert_eq!(transaction.nonce, 9);
+        assert_eq!(transaction.from, signer().1);
+        assert_eq!(transaction.to, Some(H160::repeat_byte(0x35)));
+        assert_eq!(transaction.value, U256::from(1_000_000_000_000_000_000u64));
+        assert_eq!(transaction.selector, None);
+    }
+
+    #[test]
+    fn test_recover_sender_of_typed_transactions() {
+        let selector: [u8; 4] = repay_call()[..4].try_into().unwrap();
+
+        for tx_type in [ACCESS_LIST_TX_TYPE, DYNAMIC_FEE_TX_TYPE, BLOB_TX_TYPE] {
+            let encoded = sign_typed(tx_type, &typed_fields(tx_type));
+
+            let transaction = verify_transaction(&encoded).unwrap();
+            assert_eq!(transaction.tx_type, tx_type);
+            assert_eq!(transaction.chain_id, Some(1));
+            assert_eq!(transaction.nonce, 7);
+            assert_eq!(transaction.from, signer().1);
+            assert_eq!(transaction.to, Some(lending()));
+            assert_eq!(transaction.value, U256::from(5u64));
+            assert_eq!(transaction.selector, Some(selector));
+            assert_eq!(
+                transaction.hash,
+                H256::from_slice(&Keccak256::digest(&encoded))
+            );
+        }
+    }
+
+    #[test]
+    fn test_tampered_transaction_recovers_other_sender() {
+        let mut fields = typed_fields(DYNAMIC_FEE_TX_TYPE);
+        let encoded = sign_typed(DYNAMIC_FEE_TX_TYPE, &fields);
+
+        // Same signature over a different value
+        let signed = decode_transaction(&encoded).unwrap();
+        fields[6] = item(&U256::from(6u64));
+        let mut stream = RlpStream::new_list(fields.len() + 3);
+        for field in &fields {
+            stream.append_raw(field, 1);
+        }
+        stream.append(&(signed.y_parity as u8));
+        stream.append(&signed.r);
+        stream.append(&signed.s);
+        let mut tampered = vec![DYNAMIC_FEE_TX_TYPE];
+        tampered.extend_from_slice(&stream.out());
+
+        if let Ok(transaction) = verify_transaction(&tampered) {
+            assert_ne!(transaction.from, signer().1);
+        }
+    }
+
+    #[test]
+    fn test_decode_transaction_rejects_malformed_input() {
+        assert!(decode_transaction(&[]).is_err());
+
+        // Unknown type
+        let mut encoded = sign_typed(DYNAMIC_FEE_TX_TYPE, &typed_fields(DYNAMIC_FEE_TX_TYPE));
+        encoded[0] = 0x05;
+        assert!(decode_transaction(&encoded).is_err());
+
+        // Blob transaction creating a contract
+        let mut fields = typed_fields(BLOB_TX_TYPE);
+        fields[5] = item(&Vec::<u8>::new());
+        assert!(decode_transaction(&sign_typed(BLOB_TX_TYPE, &fields)).is_err());
+
+        // Legacy transaction prefixed with type 0
+        let mut prefixed = vec![0x00];
+        prefixed.extend_from_slice(&hex::decode(EIP155_TX).unwrap());
+        assert!(decode_transaction(&prefixed).is_err());
+    }
+
+    #[test]
+    fn test_verify_transaction_proof() {
+        let legacy = hex::decode(EIP155_TX).unwrap();
+        let typed = sign_typed(DYNAMIC_FEE_TX_TYPE, &typed_fields(DYNAMIC_FEE_TX_TYPE));
+
+        // Keys rlp(0) = 0x80 and rlp(1) = 0x01 branch at the first nibble
+        let leaf = |path: u8, value: &[u8]| {
+            let mut stream = RlpStream::new_list(2);
+            stream.append(&vec![path]).append(&value.to_vec());
+            stream.out().to_vec()
+        };
+        let leaf_0 = leaf(0x30, &legacy);
+        let leaf_1 = leaf(0x31, &typed);
+        let mut root = RlpStream::new_list(17);
+        for index in 0..16 {
+            match index {
+                0 => root.append(&Keccak256::digest(&leaf_1).to_vec()),
+                8 => root.append(&Keccak256::digest(&leaf_0).to_vec()),
+                _ => root.append_empty_data(),
+            };
+        }
+        root.append_empty_data();
+        let root = root.out().to_vec();
+        let transactions_root = H256::from_slice(&Keccak256::digest(&root));
+
+        let first = verify_transaction_proof(transactions_root, 0, &[root.clone(), leaf_0])
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.nonce, 9);
+
+        let second =
+            verify_transaction_proof(transactions_root, 1, &[root.clone(), leaf_1.clone()])
+                .unwrap()
+                .unwrap();
+        assert_eq!(second.from, signer().1);
+        assert_eq!(second.to, Some(lending()));
+
+        // Proof of the second transaction presented for the first one
+        assert!(verify_transaction_proof(transactions_root, 0, &[root.clone(), leaf_1]).is_err());
+        // rlp(16) = 0x10, the root has no child for the first nibble
+        assert_eq!(
+            verify_transaction_proof(transactions_root, 16, &[root]).unwrap(),
+            None
+        );
+    }
+}
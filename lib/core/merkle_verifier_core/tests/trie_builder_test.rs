@@ -0,0 +1,181 @@
+#[cfg(test)]
+mod tests {
+    use ethereum_types::{H256, U256};
+    use merkle_verifier_core::keccak::keccak256;
+    use merkle_verifier_core::merkle_patricia::*;
+    use merkle_verifier_core::receipt::tx_index_key;
+    use merkle_verifier_core::test_utils::{MemoryTrie, StateBuilder};
+
+    fn h256(hex_str: &str) -> H256 {
+        H256::from_slice(&hex::decode(hex_str.trim_start_matches("0x")).unwrap())
+    }
+
+    fn slot(index: u64) -> [u8; 32] {
+        H256::from_low_u64_be(index).into()
+    }
+
+    fn address(index: u8) -> [u8; 20] {
+        [index; 20]
+    }
+
+    #[test]
+    fn test_known_root_with_values_in_branches() {
+        // Trie test vector shared by the Ethereum clients. "do" is a prefix of "dog" and
+        // "doge", so their values end up in branch nodes, and most nodes are embedded.
+        let mut trie = MemoryTrie::new();
+        for (key, value) in [
+            ("do", "verb"),
+            ("dog", "puppy"),
+            ("doge", "coin"),
+            ("horse", "stallion"),
+        ] {
+            trie.insert(key.as_bytes(), value.as_bytes().to_vec());
+        }
+
+        let root = trie.root();
+        assert_eq!(
+            root,
+            h256("5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84")
+        );
+
+        for key in ["do", "dog", "doge", "horse"] {
+            let proof = trie.proof(key.as_bytes());
+            assert_eq!(
+                verify_proof(root, key.as_bytes(), &proof)
+                    .unwrap()
+                    .as_deref(),
+                trie.get(key.as_bytes())
+            );
+        }
+        for key in ["d", "dogs", "cat", "horses"] {
+            let proof = trie.proof(key.as_bytes());
+            assert!(verify_trie_proof(root, key.as_bytes(), &proof).is_exclusion());
+        }
+    }
+
+    #[test]
+    fn test_transaction_sized_trie() {
+        let mut trie = MemoryTrie::new();
+        assert_eq!(trie.root(), EMPTY_TRIE_ROOT);
+        assert!(verify_proof(
+            EMPTY_TRIE_ROOT,
+            &tx_index_key(0),
+            &trie.proof(&tx_index_key(0))
+        )
+        .unwrap()
+        .is_none());
+
+        for index in 0..130u64 {
+            trie.insert(
+                &tx_index_key(index),
+                keccak256(&index.to_be_bytes()).0.to_vec(),
+            );
+        }
+        let root = trie.root();
+
+        // Keys above 127 are two bytes long
+        for index in 0..130u64 {
+            let key = tx_index_key(index);
+            let value = verify_proof(root, &key, &trie.proof(&key)).unwrap();
+            assert_eq!(value.as_deref(), trie.get(&key));
+        }
+        let missing = tx_index_key(130);
+        assert_eq!(
+            verify_proof(root, &missing, &trie.proof(&missing)),
+            Ok(None)
+        );
+
+        // A proof only holds for the key it was built for
+        let proof = trie.proof(&tx_index_key(7));
+        assert!(verify_proof(root, &tx_index_key(8), &proof).is_err());
+
+        // Removing the key restores the previous root
+        trie.insert(&missing, vec![1]);
+        assert_ne!(trie.root(), root);
+        trie.remove(&missing);
+        assert_eq!(trie.root(), root);
+    }
+
+    #[test]
+    fn test_state_proofs() {
+        let mut state = StateBuilder::new();
+        for index in 1..=50u8 {
+            state.account(address(index), index as u64, U256::from(index) * 1000);
+        }
+        state
+            .code(address(7), &[0x60, 0x00])
+            .storage(address(7), slot(0), U256::from(42))
+            .storage(address(7), slot(1), U256::MAX)
+            .storage(address(7), slot(2), U256::from(1))
+            // Zero values are not stored
+            .storage(address(7), slot(2), U256::zero());
+        let state_root = state.state_root();
+
+        let account =
+            verify_account_proof(state_root, &address(7), &state.account_proof(address(7)))
+                .unwrap()
+                .unwrap();
+        assert_eq!(account.nonce, U256::from(7));
+        assert_eq!(account.balance, U256::from(7000));
+        assert_eq!(account.code_hash, keccak256(&[0x60, 0x00]));
+        assert_eq!(account.storage_root, state.storage_root(address(7)));
+
+        let storage_proof = state.storage_proof(address(7), slot(1));
+        assert_eq!(
+            verify_storage_proof(account.storage_root, &slot(1), &storage_proof.proof),
+            Ok(Some(U256::MAX))
+        );
+        let storage_proof = state.storage_proof(address(7), slot(2));
+        assert_eq!(
+            verify_storage_proof(account.storage_root, &slot(2), &storage_proof.proof),
+            Ok(None)
+        );
+
+        // Accounts without storage have the empty trie as storage root
+        let account =
+            verify_account_proof(state_root, &address(8), &state.account_proof(address(8)))
+                .unwrap()
+                .unwrap();
+        assert_eq!(account.storage_root, EMPTY_TRIE_ROOT);
+        assert_eq!(account.code_hash, keccak256(&[]));
+
+        let missing = address(99);
+        assert!(
+            verify_account_proof(state_root, &missing, &state.account_proof(missing))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_bundle_matches_eth_get_proof() {
+        let mut state = StateBuilder::new();
+        state
+            .account(address(1), 0, U256::from(5))
+            .storage(address(1), slot(3), U256::from(9))
+            .account(address(2), 1, U256::zero());
+
+        let bundle = state.bundle(address(1), &[slot(3), slot(4)]);
+        let (account, values) = bundle.verify(state.state_root()).unwrap();
+        assert_eq!(account.unwrap().balance, U256::from(5));
+        assert_eq!(values[&slot(3)], U256::from(9));
+        assert_eq!(values[&slot(4)], U256::zero());
+
+        // Wrong state root
+        let other_root = state.storage_root(address(1));
+        assert!(bundle.verify(other_root).is_err());
+
+        // Missing accounts hold no storage
+        let bundle = state.bundle(address(3), &[slot(3)]);
+        let (account, values) = bundle.verify(state.state_root()).unwrap();
+        assert!(account.is_none());
+        assert_eq!(values[&slot(3)], U256::zero());
+
+        // Storage proofs of a missing account must be empty
+        let mut bundle = state.bundle(address(3), &[slot(3)]);
+        bundle.storage_proofs[0].proof = state.storage_proof(address(1), slot(3)).proof;
+        assert!(bundle.verify(state.state_root()).is_err());
+        bundle.storage_proofs[0].proof = vec![vec![0x80]];
+        assert!(bundle.verify(state.state_root()).is_err());
+    }
+}
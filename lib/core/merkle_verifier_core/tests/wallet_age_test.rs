@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod tests {
+    use ethereum_types::{H256, U256};
+    use merkle_verifier_core::block_header::header_hash;
+    use merkle_verifier_core::merkle_patricia::ProofError;
+    use merkle_verifier_core::test_utils::{header_chain, test_header, StateBuilder};
+    use merkle_verifier_core::wallet_age::*;
+
+    const ACTIVE: [u8; 20] = [0x11; 20];
+    const FRESH: [u8; 20] = [0x22; 20];
+
+    fn state() -> StateBuilder {
+        let mut state = StateBuilder::new();
+        state
+            .account(ACTIVE, 3, U256::from(1000))
+            .account(FRESH, 0, U256::from(5000));
+        state
+    }
+
+    // Proof of `address` at block 4_000_000, anchored to a checkpoint `length - 1` blocks
+    // later
+    fn age_proof(state: &StateBuilder, address: [u8; 20], length: u64) -> AgeProof {
+        let headers = header_chain(4_000_000, length, state.state_root());
+        AgeProof {
+            checkpoint_hash: header_hash(headers.last().unwrap()).into(),
+            headers,
+            account: state.bundle(address, &[]),
+        }
+    }
+
+    #[test]
+    fn test_active_account() {
+        let state = state();
+        let proof = age_proof(&state, ACTIVE, 3);
+        let age = proof.verify().unwrap();
+
+        assert_eq!(age.address, ACTIVE);
+        assert_eq!(age.block_number, 4_000_000);
+        assert_eq!(age.block_hash, header_hash(&proof.headers[0]));
+        assert_eq!(age.timestamp, 1_746_612_311 + 4_000_000 * 12);
+        assert_eq!(age.nonce, U256::from(3));
+        assert_eq!(age.checkpoint_number, 4_000_002);
+        assert_eq!(age.checkpoint_hash, H256::from(proof.checkpoint_hash));
+    }
+
+    #[test]
+    fn test_inactive_or_unanchored_accounts_are_rejected() {
+        let state = state();
+
+        // Funded but never sent a transaction, and not in the state at all
+        assert!(age_proof(&state, FRESH, 1).verify().is_err());
+        assert!(age_proof(&state, [0x33; 20], 1).verify().is_err());
+
+        // Checkpoint the chain does not lead to
+        let mut proof = age_proof(&state, ACTIVE, 2);
+        proof.checkpoint_hash = [0xbb; 32];
+        assert!(proof.verify().is_err());
+
+        // Checkpoint further than the EIP-2935 window
+        let proof = age_proof(&state, ACTIVE, HISTORY_SERVE_WINDOW + 2);
+        assert_eq!(
+            proof.verify(),
+            Err(ProofError::InvalidProof(
+                "Checkpoint is too far from the proven block".into()
+            ))
+        );
+        let proof = age_proof(&state, ACTIVE, HISTORY_SERVE_WINDOW + 1);
+        assert!(proof.verify().is_ok());
+
+        // Account proof against another state
+        let mut other = state.clone();
+        other.account([0x44; 20], 1, U256::one());
+        let mut proof = age_proof(&state, ACTIVE, 1);
+        proof.account = other.bundle(ACTIVE, &[]);
+        assert!(proof.verify().is_err());
+    }
+
+    #[test]
+    fn test_wallet_ages() {
+        let state = state();
+        let mut late_state = state.clone();
+        late_state.account([0x55; 20], 1, U256::zero());
+
+        let mut late = age_proof(&late_state, [0x55; 20], 1);
+        let early = age_proof(&state, ACTIVE, 2);
+        // A later block for the second account
+        let late_header = test_header(H256::zero(), 5_000_000, late_state.state_root());
+        late.headers = vec![rlp::encode(&late_header).to_vec()];
+        late.checkpoint_hash = late_header.hash().into();
+
+        let proofs = vec![late, early];
+        let encoded = encode_age_proofs(&proofs);
+        assert_eq!(decode_age_proofs(&encoded), Ok(proofs.clone()));
+        assert!(decode_age_proofs(&encoded[..encoded.len() - 1]).is_err());
+
+        let ages = verify_wallet_ages(&proofs).unwrap();
+        assert_eq!(ages.len(), 2);
+        let earliest = earliest_active(&ages).unwrap();
+        assert_eq!(earliest.address, ACTIVE);
+        assert_eq!(earliest.block_number, 4_000_000);
+        assert_eq!(earliest_active(&[]), None);
+
+        // The verifier only accepts the checkpoints it trusts
+        let trusted: Vec<Checkpoint> = ages
+            .iter()
+            .map(|age| Checkpoint {
+                number: age.checkpoint_number,
+                hash: age.checkpoint_hash,
+            })
+            .collect();
+        assert!(check_checkpoints(&ages, &trusted).is_ok());
+        assert!(check_checkpoints(&ages, &trusted[..1]).is_err());
+        let mut wrong_number = trusted.clone();
+        wrong_number[1].number += 1;
+        assert!(check_checkpoints(&ages, &wrong_number).is_err());
+
+        // Every account is proven once
+        let duplicate = vec![proofs[1].clone(), proofs[1].clone()];
+        assert!(verify_wallet_ages(&duplicate).is_err());
+    }
+
+    #[test]
+    fn test_checkpoint_selection() {
+        let checkpoint = |number| Checkpoint {
+            number,
+            hash: H256::repeat_byte(0xcc),
+        };
+        let trusted = [checkpoint(8_192), checkpoint(16_384), checkpoint(24_576)];
+
+        // Nearest registered checkpoint at or after the block
+        assert_eq!(checkpoint_for(100, 20_000_000, &trusted), Some(8_192));
+        assert_eq!(checkpoint_for(8_192, 20_000_000, &trusted), Some(8_192));
+        assert_eq!(checkpoint_for(8_193, 20_000_000, &trusted), Some(16_384));
+
+        // Recent blocks fall back to the latest block
+        assert_eq!(
+            checkpoint_for(19_995_000, 20_000_000, &trusted),
+            Some(20_000_000)
+        );
+        assert_eq!(
+            checkpoint_for(19_991_809, 20_000_000, &[]),
+            Some(20_000_000)
+        );
+
+        // Old blocks without a registered checkpoint in the window
+        assert_eq!(checkpoint_for(19_991_808, 20_000_000, &[]), None);
+        assert_eq!(checkpoint_for(30_000, 20_000_000, &trusted), None);
+    }
+}
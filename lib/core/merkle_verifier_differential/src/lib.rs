@@ -0,0 +1,2 @@
+// Only holds the differential tests in tests/, which need a reference trie implementation
+// merkle_verifier_core does not depend on.
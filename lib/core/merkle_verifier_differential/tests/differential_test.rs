@@ -0,0 +1,227 @@
+#[cfg(test)]
+mod tests {
+    use ethereum_types::H256;
+    use merkle_verifier_core::keccak::keccak256;
+    use merkle_verifier_core::merkle_patricia::*;
+    use merkle_verifier_core::multiproof::NodeCache;
+    use merkle_verifier_core::test_utils::MemoryTrie;
+    use risc0_ethereum_trie::Trie;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::Once;
+
+    // Shapes of the random tries: key length in bytes and number of keys. The risc0 trie
+    // has no values in branch nodes, so all keys of a trie have the same length. Short
+    // keys give extension nodes and embedded nodes, 32 byte keys look like state tries.
+    const SHAPES: [(usize, usize); 6] = [(1, 1), (1, 40), (2, 3), (2, 150), (3, 60), (32, 100)];
+    const SEEDS: u64 = 4;
+
+    // xorshift64*, so every run checks the same tries
+    struct Rng(u64);
+
+    impl Rng {
+        fn new(seed: u64) -> Self {
+            Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+        }
+
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        fn below(&mut self, bound: usize) -> usize {
+            (self.next() % bound as u64) as usize
+        }
+
+        fn bytes(&mut self, len: usize) -> Vec<u8> {
+            (0..len).map(|_| self.next() as u8).collect()
+        }
+    }
+
+    struct RandomTrie {
+        trie: MemoryTrie,
+        root: H256,
+        keys: Vec<Vec<u8>>,
+        // Keys of the same length that are not in the trie
+        missing: Vec<Vec<u8>>,
+    }
+
+    // Values are between 1 and 40 bytes, so leaves are embedded in their parent or not
+    fn random_trie(rng: &mut Rng, key_len: usize, count: usize) -> RandomTrie {
+        let mut trie = MemoryTrie::new();
+        let mut keys = Vec::new();
+        while trie.len() < count {
+            let key = match key_len {
+                32 => keccak256(&rng.next().to_be_bytes()).0.to_vec(),
+                _ => rng.bytes(key_len),
+            };
+            if trie.get(&key).is_none() {
+                keys.push(key.clone());
+            }
+            let value_len = 1 + rng.below(40);
+            trie.insert(&key, rng.bytes(value_len));
+        }
+        keys.truncate(20);
+
+        let mut missing = Vec::new();
+        while missing.len() < 10 {
+            let key = rng.bytes(key_len);
+            if trie.get(&key).is_none() {
+                missing.push(key);
+            }
+        }
+
+        RandomTrie {
+            root: trie.root(),
+            trie,
+            keys,
+            missing,
+        }
+    }
+
+    // The risc0 trie panics on paths through nodes the proof does not contain, those
+    // expected panics are kept out of the test output
+    fn silence_reference_panics() {
+        static HOOK: Once = Once::new();
+        HOOK.call_once(|| {
+            let default_hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                let message = info.payload().downcast_ref::<&str>();
+                if !message.is_some_and(|message| message.starts_with("MPT: ")) {
+                    default_hook(info);
+                }
+            }));
+        });
+    }
+
+    // Verify with the risc0 trie: rebuild the sparse trie from the proof nodes, check its
+    // root and look the key up. `None` if the proof does not prove anything about the key.
+    //
+    // The root is checked against the hash of the raw root node. `Trie::hash_slow` hashes
+    // the re-encoded root, which also matches for a root node with non-canonical padding.
+    fn reference_verify(root: H256, key: &[u8], proof: &[Vec<u8>]) -> Option<Option<Vec<u8>>> {
+        silence_reference_panics();
+
+        let root_hash = proof
+            .first()
+            .map_or(EMPTY_TRIE_ROOT, |node| keccak256(node));
+        if root_hash != root {
+            return None;
+        }
+        let trie = Trie::from_rlp(proof).ok()?;
+        panic::catch_unwind(AssertUnwindSafe(|| trie.get(key).map(<[u8]>::to_vec))).ok()
+    }
+
+    // Both verifiers have to accept the proof with the same value, or both reject it
+    fn assert_agree(root: H256, key: &[u8], proof: &[Vec<u8>]) -> Option<Option<Vec<u8>>> {
+        let ours = verify_proof(root, key, proof).ok();
+        let reference = reference_verify(root, key, proof);
+        assert_eq!(
+            ours,
+            reference,
+            "verifiers disagree on key {} with proof {:?}",
+            hex::encode(key),
+            proof.iter().map(hex::encode).collect::<Vec<_>>()
+        );
+        ours
+    }
+
+    fn for_each_trie(mut check: impl FnMut(&mut Rng, &RandomTrie)) {
+        for seed in 0..SEEDS {
+            for (key_len, count) in SHAPES {
+                let mut rng = Rng::new(seed << 8 | (key_len * count) as u64);
+                let trie = random_trie(&mut rng, key_len, count);
+                check(&mut rng, &trie);
+            }
+        }
+    }
+
+    #[test]
+    fn test_valid_proofs_agree() {
+        for_each_trie(|_, random| {
+            for key in &random.keys {
+                let proof = random.trie.proof(key);
+                let value = assert_agree(random.root, key, &proof);
+                assert_eq!(value, Some(random.trie.get(key).map(<[u8]>::to_vec)));
+            }
+            for key in &random.missing {
+                let proof = random.trie.proof(key);
+                assert_eq!(assert_agree(random.root, key, &proof), Some(None));
+            }
+        });
+
+        // The empty trie proves the exclusion of every key with an empty proof
+        assert_eq!(assert_agree(EMPTY_TRIE_ROOT, &[1, 2], &[]), Some(None));
+    }
+
+    #[test]
+    fn test_mutated_proofs_agree() {
+        for_each_trie(|rng, random| {
+            for key in random.keys.iter().chain(&random.missing) {
+                let proof = random.trie.proof(key);
+                let index = rng.below(proof.len());
+
+                // Flip a random bit of a random node
+                let mut mutated = proof.clone();
+                let byte = rng.below(mutated[index].len());
+                mutated[index][byte] ^= 1 << rng.below(8);
+                assert_eq!(assert_agree(random.root, key, &mutated), None);
+
+                // Cut a node short
+                let mut mutated = proof.clone();
+                let len = rng.below(mutated[index].len());
+                mutated[index].truncate(len);
+                assert_eq!(assert_agree(random.root, key, &mutated), None);
+
+                // Drop a node from the middle of the proof
+                if proof.len() > 1 {
+                    let mut mutated = proof.clone();
+                    mutated.remove(index);
+                    assert_eq!(assert_agree(random.root, key, &mutated), None);
+                }
+
+                // Right proof, wrong root
+                let wrong_root = keccak256(random.root.as_bytes());
+                assert_eq!(assert_agree(wrong_root, key, &proof), None);
+            }
+        });
+    }
+
+    #[test]
+    fn test_truncated_proofs_agree() {
+        for_each_trie(|_, random| {
+            for key in random.keys.iter().chain(&random.missing) {
+                let proof = random.trie.proof(key);
+                for len in 0..proof.len() {
+                    assert_eq!(assert_agree(random.root, key, &proof[..len]), None);
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn test_proofs_of_other_keys() {
+        // A proof of one key can prove something about another key that shares its path.
+        // The risc0 trie ignores nodes it does not need, like `NodeCache::verify`, while
+        // `verify_proof` rejects the proof if any of its nodes is unused.
+        for_each_trie(|rng, random| {
+            let keys: Vec<&Vec<u8>> = random.keys.iter().chain(&random.missing).collect();
+            for key in &keys {
+                let other = keys[rng.below(keys.len())];
+                let proof = random.trie.proof(other);
+
+                let reference = reference_verify(random.root, key, &proof);
+                let cached = NodeCache::from_proofs([proof.as_slice()])
+                    .verify(random.root, key)
+                    .into_result()
+                    .ok();
+                assert_eq!(cached, reference);
+
+                if let Ok(value) = verify_proof(random.root, key, &proof) {
+                    assert_eq!(Some(value), reference);
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,313 @@
+// Scorecard CLI: score a `CreditInput` without writing a test for it.
+//
+// usage: score [--format table|json] [--batch] [--policy POLICY] [FILE]
+//   FILE      JSON encoded CreditInput, `-` or no FILE reads from stdin
+//   --batch   input is JSONL, one CreditInput per line
+//   --policy  JSON encoded ScoringPolicy to score with, the default policy otherwise
+//
+// e.g.  cargo run -p score_calculation --bin score -- --format json input.json
+
+use score_calculation::policy::ScoringPolicy;
+use score_calculation::{
+    calculate_credit_limit, calculate_credit_score_with_policy,
+    calculate_score_contributions_with_policy, CreditInput, CreditScoreBreakdown,
+    ScoreContributions,
+};
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Read};
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: score [--format table|json] [--batch] [--policy POLICY] [FILE]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Table,
+    Json,
+}
+
+struct Args {
+    format: OutputFormat,
+    batch: bool,
+    policy_path: Option<String>,
+    input_path: Option<String>,
+}
+
+enum Command {
+    Score(Args),
+    Help,
+}
+
+/// Everything printed for a single scored input
+#[derive(Debug, Serialize)]
+struct ScoreReport {
+    breakdown: CreditScoreBreakdown,
+    credit_limit_wei: u128,
+    contributions: ScoreContributions,
+}
+
+/// Result line of the batch mode. `line` is 1-based.
+#[derive(Debug, Serialize)]
+struct BatchEntry {
+    line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    report: Option<ScoreReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+fn parse_args() -> Result<Command, String> {
+    let mut args = Args {
+        format: OutputFormat::Table,
+        batch: false,
+        policy_path: None,
+        input_path: None,
+    };
+
+    let mut raw = std::env::args().skip(1);
+    while let Some(arg) = raw.next() {
+        match arg.as_str() {
+            "--format" | "-f" => {
+                args.format = match raw.next().as_deref() {
+                    Some("table") => OutputFormat::Table,
+                    Some("json") => OutputFormat::Json,
+                    other => return Err(format!("Unknown output format: {:?}", other)),
+                }
+            }
+            "--batch" | "-b" => args.batch = true,
+            "--policy" | "-p" => {
+                args.policy_path = Some(raw.next().ok_or("Missing policy file")?);
+            }
+            "--help" | "-h" => return Ok(Command::Help),
+            path if args.input_path.is_none() => args.input_path = Some(path.to_string()),
+            other => return Err(format!("Unexpected argument: {}", other)),
+        }
+    }
+
+    Ok(Command::Score(args))
+}
+
+fn read_input(path: Option<&str>) -> Result<String, String> {
+    match path {
+        None | Some("-") => {
+            let mut buffer = String::new();
+            io::stdin()
+                .read_to_string(&mut buffer)
+                .map_err(|e| format!("Failed to read stdin: {}", e))?;
+            Ok(buffer)
+        }
+        Some(path) => {
+            fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))
+        }
+    }
+}
+
+fn read_policy(path: Option<&str>) -> Result<ScoringPolicy, String> {
+    let Some(path) = path else {
+        return Ok(ScoringPolicy::default());
+    };
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid ScoringPolicy: {}", e))
+}
+
+fn score(input: &CreditInput, policy: &ScoringPolicy) -> Result<ScoreReport, String> {
+    let breakdown = calculate_credit_score_with_policy(input, policy)?;
+    let contributions = calculate_score_contributions_with_policy(&breakdown, policy);
+    Ok(ScoreReport {
+        breakdown,
+        credit_limit_wei: calculate_credit_limit(input.total_eth_balance, input.trust_level),
+        contributions,
+    })
+}
+
+fn print_table(report: &ScoreReport, policy: &ScoringPolicy) {
+    let b = &report.breakdown;
+    let c = &report.contributions;
+    let rows = [
+        (
+            "Payment history",
+            b.payment_history_score,
+            policy.weight(policy.payment_history_weight_bps),
+            c.payment_history,
+        ),
+        (
+            "Credit utilization",
+            b.credit_utilization_score,
+            policy.weight(policy.credit_utilization_weight_bps),
+            c.credit_utilization,
+        ),
+        (
+            "Tradify integration",
+            b.tradify_integration_score,
+            policy.weight(policy.tradify_integration_weight_bps),
+            c.tradify_integration,
+        ),
+        (
+            "Length of history",
+            b.length_of_history_score,
+            policy.weight(policy.length_of_history_weight_bps),
+            c.length_of_history,
+        ),
+        (
+            "Trust factor",
+            b.trust_factor_score,
+            policy.weight(policy.trust_factor_weight_bps),
+            c.trust_factor,
+        ),
+    ];
+
+    println!(
+        "{:<22} {:>6} {:>7} {:>13}",
+        "Component", "Score", "Weight", "Contribution"
+    );
+    println!("{}", "-".repeat(51));
+    for (name, score, weight, contribution) in rows {
+        println!(
+            "{:<22} {:>6} {:>6.0}% {:>13.2}",
+            name,
+            score,
+            weight * 100.0,
+            contribution
+        );
+    }
+    println!("{}", "-".repeat(51));
+    println!("{:<22} {:>6}", "Final score", b.final_score);
+    println!("{:<22} {:>6}", "Policy version", policy.version);
+    println!(
+        "{:<22} {:>28}",
+        "Credit limit (wei)", report.credit_limit_wei
+    );
+}
+
+fn print_batch_table_header() {
+    println!(
+        "{:>6} {:>6} {:>8} {:>12} {:>8} {:>7} {:>6} {:>26}",
+        "line",
+        "final",
+        "payment",
+        "utilization",
+        "tradify",
+        "length",
+        "trust",
+        "credit limit (wei)"
+    );
+}
+
+fn print_batch_table_row(entry: &BatchEntry) {
+    match (&entry.report, &entry.error) {
+        (Some(report), _) => {
+            let b = &report.breakdown;
+            println!(
+                "{:>6} {:>6} {:>8} {:>12} {:>8} {:>7} {:>6} {:>26}",
+                entry.line,
+                b.final_score,
+                b.payment_history_score,
+                b.credit_utilization_score,
+                b.tradify_integration_score,
+                b.length_of_history_score,
+                b.trust_factor_score,
+                report.credit_limit_wei
+            );
+        }
+        (None, Some(error)) => println!("{:>6} error: {}", entry.line, error),
+        (None, None) => {}
+    }
+}
+
+fn run_single(args: &Args, policy: &ScoringPolicy, content: &str) -> Result<(), String> {
+    let input: CreditInput =
+        serde_json::from_str(content).map_err(|e| format!("Invalid CreditInput: {}", e))?;
+    let report = score(&input, policy)?;
+
+    match args.format {
+        OutputFormat::Table => print_table(&report, policy),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?
+        ),
+    }
+    Ok(())
+}
+
+/// Score every non-empty line. Failing records are reported and do not stop the batch.
+/// Returns the number of failed records.
+fn run_batch(args: &Args, policy: &ScoringPolicy, content: &str) -> Result<usize, String> {
+    let mut failures = 0;
+
+    if args.format == OutputFormat::Table {
+        print_batch_table_header();
+    }
+
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let result = serde_json::from_str::<CreditInput>(line)
+            .map_err(|e| format!("Invalid CreditInput: {}", e))
+            .and_then(|input| score(&input, policy));
+
+        let entry = match result {
+            Ok(report) => BatchEntry {
+                line: index + 1,
+                report: Some(report),
+                error: None,
+            },
+            Err(error) => {
+                failures += 1;
+                BatchEntry {
+                    line: index + 1,
+                    report: None,
+                    error: Some(error),
+                }
+            }
+        };
+
+        match args.format {
+            OutputFormat::Table => print_batch_table_row(&entry),
+            OutputFormat::Json => println!(
+                "{}",
+                serde_json::to_string(&entry).map_err(|e| e.to_string())?
+            ),
+        }
+    }
+
+    Ok(failures)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(Command::Score(args)) => args,
+        Ok(Command::Help) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let result = read_policy(args.policy_path.as_deref()).and_then(|policy| {
+        let content = read_input(args.input_path.as_deref())?;
+        if args.batch {
+            run_batch(&args, &policy, &content).inspect(|&failures| {
+                if failures > 0 {
+                    eprintln!("{} record(s) failed to score", failures);
+                }
+            })
+        } else {
+            run_single(&args, &policy, &content).map(|_| 0)
+        }
+    });
+
+    match result {
+        Ok(0) => ExitCode::SUCCESS,
+        Ok(_) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
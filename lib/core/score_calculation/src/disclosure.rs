@@ -0,0 +1,152 @@
+use crate::policy::{
+    calculate_credit_score_with_signed_policy, PolicyCommitment, PolicyPins, PolicyScoreOutput,
+    SignedScoringPolicy,
+};
+use crate::{CreditInput, CreditScoreBreakdown};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// Domain separator of the breakdown commitment
+const BREAKDOWN_COMMITMENT_DOMAIN: &[u8] = b"risc_zero_banking.breakdown_commitment.v1";
+
+const MIN_SCORE: u16 = 300;
+const MAX_SCORE: u16 = 850;
+
+/// What the scoring pipeline reveals about the calculated score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisclosureMode {
+    /// Reveal the whole breakdown
+    Full,
+    /// Only reveal whether `final_score >= threshold`, the threshold is chosen by the lender
+    Threshold { threshold: u16 },
+    /// Only reveal the band the final score falls into. Bands start at 300 and are
+    /// `width` points wide, e.g. a width of 50 gives 700-749.
+    Band { width: u16 },
+}
+
+/// Revealed part of the score
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Disclosure {
+    Full(CreditScoreBreakdown),
+    Threshold {
+        threshold: u16,
+        met: bool,
+    },
+    /// Inclusive bounds of the band
+    Band {
+        lower: u16,
+        upper: u16,
+    },
+}
+
+/// Output to commit to the journal: the revealed part plus a hiding commitment to the
+/// full breakdown that the borrower can open later, and the policy the score was
+/// calculated with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisclosureOutput {
+    pub disclosure: Disclosure,
+    pub breakdown_commitment: [u8; 32],
+    pub policy: PolicyCommitment,
+}
+
+/// Data needed to open a breakdown commitment. Kept private by the borrower.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BreakdownOpening {
+    pub breakdown: CreditScoreBreakdown,
+    pub blinding: [u8; 32],
+}
+
+impl DisclosureMode {
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            DisclosureMode::Full => Ok(()),
+            DisclosureMode::Threshold { threshold } => {
+                if !(MIN_SCORE..=MAX_SCORE).contains(&threshold) {
+                    return Err("Disclosure threshold must be between 300-850".to_string());
+                }
+                Ok(())
+            }
+            DisclosureMode::Band { width } => {
+                if width == 0 {
+                    return Err("Disclosure band width must be greater than zero".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// keccak256(domain || breakdown fields (big endian, declaration order) || blinding)
+///
+/// The score space is tiny, so without a secret blinding the commitment could be opened by
+/// brute force. The blinding has to be 32 random bytes known only to the borrower.
+pub fn commit_breakdown(breakdown: &CreditScoreBreakdown, blinding: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(BREAKDOWN_COMMITMENT_DOMAIN);
+    hasher.update(breakdown.length_of_history_score.to_be_bytes());
+    hasher.update(breakdown.payment_history_score.to_be_bytes());
+    hasher.update(breakdown.credit_utilization_score.to_be_bytes());
+    hasher.update(breakdown.tradify_integration_score.to_be_bytes());
+    hasher.update(breakdown.trust_factor_score.to_be_bytes());
+    hasher.update(breakdown.final_score.to_be_bytes());
+    hasher.update(blinding);
+    hasher.finalize().into()
+}
+
+/// Check that an opening matches a previously committed breakdown
+pub fn verify_breakdown_opening(commitment: &[u8; 32], opening: &BreakdownOpening) -> bool {
+    commit_breakdown(&opening.breakdown, &opening.blinding) == *commitment
+}
+
+/// Band containing `score`, capped at 850
+fn score_band(score: u16, width: u16) -> (u16, u16) {
+    let index = (score.saturating_sub(MIN_SCORE)) / width;
+    let lower = MIN_SCORE.saturating_add(index.saturating_mul(width));
+    let upper = lower.saturating_add(width - 1).min(MAX_SCORE);
+    (lower, upper)
+}
+
+/// Reduce a score calculated with a verified policy to the requested disclosure
+pub fn disclose(
+    score: &PolicyScoreOutput,
+    mode: DisclosureMode,
+    blinding: &[u8; 32],
+) -> Result<DisclosureOutput, String> {
+    mode.validate()?;
+    let breakdown = &score.breakdown;
+
+    if blinding.iter().all(|&b| b == 0) {
+        return Err("Commitment blinding must not be zero".to_string());
+    }
+
+    let disclosure = match mode {
+        DisclosureMode::Full => Disclosure::Full(breakdown.clone()),
+        DisclosureMode::Threshold { threshold } => Disclosure::Threshold {
+            threshold,
+            met: breakdown.final_score >= threshold,
+        },
+        DisclosureMode::Band { width } => {
+            let (lower, upper) = score_band(breakdown.final_score, width);
+            Disclosure::Band { lower, upper }
+        }
+    };
+
+    Ok(DisclosureOutput {
+        disclosure,
+        breakdown_commitment: commit_breakdown(breakdown, blinding),
+        policy: score.policy.clone(),
+    })
+}
+
+/// Calculate the score with a governance signed policy and only disclose what the lender
+/// asked for
+pub fn calculate_credit_score_disclosed(
+    input: &CreditInput,
+    signed: &SignedScoringPolicy,
+    pins: &PolicyPins,
+    mode: DisclosureMode,
+    blinding: &[u8; 32],
+) -> Result<DisclosureOutput, String> {
+    let score = calculate_credit_score_with_signed_policy(input, signed, pins)?;
+    disclose(&score, mode, blinding)
+}
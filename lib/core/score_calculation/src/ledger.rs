@@ -0,0 +1,109 @@
+use crate::CreditScoreBreakdown;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// Single score observation stored in the ledger
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoreRecord {
+    /// Borrower identifier (usually the hex encoded address)
+    pub borrower: String,
+    /// Unix timestamp at which the score was calculated
+    pub timestamp: u64,
+    /// Debt at the time of the observation (in wei), used for trend analysis
+    pub current_debt: u128,
+    /// Score breakdown calculated at `timestamp`
+    pub breakdown: CreditScoreBreakdown,
+}
+
+/// Storage for past score observations, keyed by borrower
+pub trait ScoreLedger {
+    /// Append a new observation. Observations for a borrower have to be recorded in
+    /// chronological order.
+    fn record(&mut self, record: ScoreRecord) -> Result<(), String>;
+
+    /// All observations of a borrower, oldest first
+    fn history(&self, borrower: &str) -> Result<Vec<ScoreRecord>, String>;
+
+    /// Most recent observation of a borrower
+    fn latest(&self, borrower: &str) -> Result<Option<ScoreRecord>, String> {
+        Ok(self.history(borrower)?.pop())
+    }
+}
+
+/// Ledger persisted as a JSON Lines file, one `ScoreRecord` per line.
+/// Records are only ever appended, so the file doubles as an audit log.
+pub struct FileScoreLedger {
+    path: PathBuf,
+}
+
+impl FileScoreLedger {
+    /// Open a ledger at `path`. The file is created on the first `record` call.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn read_all(&self) -> Result<Vec<ScoreRecord>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let file = fs::File::open(&self.path)
+            .map_err(|e| format!("Failed to open score ledger: {}", e))?;
+
+        let mut records = Vec::new();
+        for (line_number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| format!("Failed to read score ledger: {}", e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: ScoreRecord = serde_json::from_str(&line).map_err(|e| {
+                format!(
+                    "Corrupted score ledger entry on line {}: {}",
+                    line_number + 1,
+                    e
+                )
+            })?;
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+}
+
+impl ScoreLedger for FileScoreLedger {
+    fn record(&mut self, record: ScoreRecord) -> Result<(), String> {
+        if let Some(last) = self.latest(&record.borrower)? {
+            if record.timestamp <= last.timestamp {
+                return Err("Score record must be newer than the latest recorded score".to_string());
+            }
+        }
+
+        let line = serde_json::to_string(&record)
+            .map_err(|e| format!("Failed to serialize score record: {}", e))?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open score ledger: {}", e))?;
+        writeln!(file, "{}", line).map_err(|e| format!("Failed to write score ledger: {}", e))
+    }
+
+    fn history(&self, borrower: &str) -> Result<Vec<ScoreRecord>, String> {
+        let mut records: Vec<ScoreRecord> = self
+            .read_all()?
+            .into_iter()
+            .filter(|record| record.borrower == borrower)
+            .collect();
+        records.sort_by_key(|record| record.timestamp);
+        Ok(records)
+    }
+}
@@ -1,5 +1,13 @@
 use serde::{Deserialize, Serialize};
 
+pub mod disclosure;
+pub mod ledger;
+pub mod loan_schedule;
+pub mod policy;
+pub mod trend;
+
+use policy::ScoringPolicy;
+
 /// Trust verification levels for data validation
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TrustLevel {
@@ -36,7 +44,7 @@ impl TrustLevel {
 }
 
 /// Payment history summary
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PaymentHistory {
     /// Total number of loans paid on time
     pub on_time_payments: u32,
@@ -64,7 +72,7 @@ pub struct CreditInput {
 }
 
 /// Detailed credit score breakdown
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CreditScoreBreakdown {
     pub length_of_history_score: u16,
     pub payment_history_score: u16,
@@ -76,14 +84,23 @@ pub struct CreditScoreBreakdown {
 
 /// Calculate comprehensive credit score
 pub fn calculate_credit_score(input: &CreditInput) -> Result<CreditScoreBreakdown, String> {
+    calculate_credit_score_with_policy(input, &ScoringPolicy::default())
+}
+
+/// Calculate comprehensive credit score with the parameters of the given policy
+pub fn calculate_credit_score_with_policy(
+    input: &CreditInput,
+    policy: &ScoringPolicy,
+) -> Result<CreditScoreBreakdown, String> {
     // Validate input
     validate_input(input)?;
+    policy.validate()?;
 
     // Calculate individual components
-    let length_score = calculate_length_of_history_score(input);
-    let payment_score = calculate_payment_history_score(input);
+    let length_score = calculate_length_of_history_score(input, policy);
+    let payment_score = calculate_payment_history_score(input, policy);
     let utilization_score = calculate_credit_utilization_score(input);
-    let tradify_score = calculate_tradify_integration_score(input);
+    let tradify_score = calculate_tradify_integration_score(input, policy);
     let trust_score = calculate_trust_factor_score(input);
 
     // Calculate weighted final score
@@ -93,6 +110,7 @@ pub fn calculate_credit_score(input: &CreditInput) -> Result<CreditScoreBreakdow
         utilization_score,
         tradify_score,
         trust_score,
+        policy,
     );
 
     Ok(CreditScoreBreakdown {
@@ -127,7 +145,7 @@ fn validate_input(input: &CreditInput) -> Result<(), String> {
 
 /// Calculate length of credit history score (10% weight)
 /// Score: 300-850 based on account age
-fn calculate_length_of_history_score(input: &CreditInput) -> u16 {
+fn calculate_length_of_history_score(input: &CreditInput, policy: &ScoringPolicy) -> u16 {
     const SECONDS_PER_DAY: u64 = 86400;
     const MIN_SCORE: u16 = 300;
     const MAX_SCORE: u16 = 850;
@@ -135,14 +153,14 @@ fn calculate_length_of_history_score(input: &CreditInput) -> u16 {
     let account_age_seconds = input.current_timestamp - input.first_interaction_timestamp;
     let account_age_days = account_age_seconds / SECONDS_PER_DAY;
 
-    // Score improves over time, max score at 2+ years (730 days)
+    // Score improves over time, max score at 2+ years (730 days by default)
     let score = if account_age_days == 0 {
         MIN_SCORE
-    } else if account_age_days >= 730 {
+    } else if account_age_days >= policy.full_history_days {
         MAX_SCORE
     } else {
         // NOTE: adding some kind of contstant factor could be done here
-        let progress = account_age_days as f64 / 730.0;
+        let progress = account_age_days as f64 / policy.full_history_days as f64;
         MIN_SCORE + ((MAX_SCORE - MIN_SCORE) as f64 * progress) as u16
     };
 
@@ -151,14 +169,14 @@ fn calculate_length_of_history_score(input: &CreditInput) -> u16 {
 
 /// Calculate payment history score (30% weight)
 /// Score based on ratio of on-time payments to liquidations
-fn calculate_payment_history_score(input: &CreditInput) -> u16 {
+fn calculate_payment_history_score(input: &CreditInput, policy: &ScoringPolicy) -> u16 {
     const MIN_SCORE: u16 = 300;
     const MAX_SCORE: u16 = 850;
 
     let total_loans = input.payment_history.on_time_payments + input.payment_history.liquidations;
 
     if total_loans == 0 {
-        return 650; // Neutral score for no history
+        return policy.neutral_payment_score; // Neutral score for no history
     }
 
     // Calculate success rate
@@ -182,7 +200,9 @@ fn calculate_payment_history_score(input: &CreditInput) -> u16 {
     let liquidation_penalty = if input.payment_history.liquidations > 0 {
         // Penalty increases with more liquidations, but caps at 150 points
         // NOTE: the cap should be discussed
-        let penalty = (input.payment_history.liquidations as f64 * 25.0).min(150.0) as u16;
+        let penalty = (input.payment_history.liquidations as f64
+            * policy.liquidation_penalty as f64)
+            .min(policy.max_liquidation_penalty as f64) as u16;
         penalty
     } else {
         0
@@ -225,10 +245,10 @@ fn calculate_credit_utilization_score(input: &CreditInput) -> u16 {
 }
 
 /// Calculate off-chain credit integration score (15% weight)
-fn calculate_tradify_integration_score(input: &CreditInput) -> u16 {
+fn calculate_tradify_integration_score(input: &CreditInput, policy: &ScoringPolicy) -> u16 {
     match input.tradify_credit_score {
         Some(score) => score,
-        None => 650, // Neutral score if no off-chain data provided
+        None => policy.neutral_tradify_score, // Neutral score if no off-chain data provided
     }
 }
 
@@ -252,6 +272,41 @@ fn calculate_trust_factor_score(input: &CreditInput) -> u16 {
     (adjusted_score + trust_bonus).min(MAX_SCORE).max(MIN_SCORE)
 }
 
+/// Contribution of each component to the weighted final score (score * weight)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreContributions {
+    pub length_of_history: f64,
+    pub payment_history: f64,
+    pub credit_utilization: f64,
+    pub tradify_integration: f64,
+    pub trust_factor: f64,
+}
+
+/// Split a breakdown into the weighted contributions of its components
+pub fn calculate_score_contributions(breakdown: &CreditScoreBreakdown) -> ScoreContributions {
+    calculate_score_contributions_with_policy(breakdown, &ScoringPolicy::default())
+}
+
+/// Split a breakdown into the weighted contributions of its components, with the weights
+/// of the policy that calculated it
+pub fn calculate_score_contributions_with_policy(
+    breakdown: &CreditScoreBreakdown,
+    policy: &ScoringPolicy,
+) -> ScoreContributions {
+    ScoreContributions {
+        length_of_history: breakdown.length_of_history_score as f64
+            * policy.weight(policy.length_of_history_weight_bps),
+        payment_history: breakdown.payment_history_score as f64
+            * policy.weight(policy.payment_history_weight_bps),
+        credit_utilization: breakdown.credit_utilization_score as f64
+            * policy.weight(policy.credit_utilization_weight_bps),
+        tradify_integration: breakdown.tradify_integration_score as f64
+            * policy.weight(policy.tradify_integration_weight_bps),
+        trust_factor: breakdown.trust_factor_score as f64
+            * policy.weight(policy.trust_factor_weight_bps),
+    }
+}
+
 /// Calculate weighted final score
 fn calculate_weighted_score(
     length_score: u16,
@@ -259,15 +314,16 @@ fn calculate_weighted_score(
     utilization_score: u16,
     tradify_score: u16,
     trust_score: u16,
+    policy: &ScoringPolicy,
 ) -> u16 {
     const MIN_SCORE: u16 = 300;
     const MAX_SCORE: u16 = 850;
 
-    let weighted_sum = (payment_score as f64 * 0.30) +     // 30%
-        (utilization_score as f64 * 0.30) + // 30%
-        (tradify_score as f64 * 0.15) +    // 15%
-        (length_score as  f64 * 0.15) +    // 10%
-        (trust_score as f64 * 0.10); // 10%
+    let weighted_sum = (payment_score as f64 * policy.weight(policy.payment_history_weight_bps))
+        + (utilization_score as f64 * policy.weight(policy.credit_utilization_weight_bps))
+        + (tradify_score as f64 * policy.weight(policy.tradify_integration_weight_bps))
+        + (length_score as f64 * policy.weight(policy.length_of_history_weight_bps))
+        + (trust_score as f64 * policy.weight(policy.trust_factor_weight_bps));
 
     let final_score = weighted_sum as u16;
     final_score.min(MAX_SCORE).max(MIN_SCORE)
@@ -0,0 +1,255 @@
+//! Loan schedule engine: classifies the installments of a loan based on the repayment and
+//! liquidation events and aggregates the result into the `PaymentHistory` used for scoring.
+//!
+//! Everything here is integer only and does not touch the file system, so the exact same
+//! classification can be executed inside a RISC Zero guest.
+
+use crate::PaymentHistory;
+use serde::{Deserialize, Serialize};
+
+/// Terms of a single loan. The principal is split into equal installments, one per due
+/// date; the rounding remainder is added to the last installment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoanTerms {
+    /// Borrowed amount (in wei)
+    pub principal: u128,
+    /// Unix timestamps at which the installments are due, strictly increasing
+    pub due_timestamps: Vec<u64>,
+    /// Seconds after a due date in which a payment still counts as on time
+    pub grace_period: u64,
+}
+
+/// Event affecting a loan, as emitted by the lending contract
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LoanEvent {
+    Repayment { timestamp: u64, amount: u128 },
+    Liquidation { timestamp: u64 },
+}
+
+impl LoanEvent {
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            LoanEvent::Repayment { timestamp, .. } => *timestamp,
+            LoanEvent::Liquidation { timestamp } => *timestamp,
+        }
+    }
+}
+
+/// Classification of a single installment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InstallmentStatus {
+    /// Fully paid before the end of the grace period
+    OnTime,
+    /// Fully paid, but after the grace period
+    Late,
+    /// Not fully paid and the grace period is over
+    Missed,
+    /// Not fully paid when the loan was liquidated
+    Liquidated,
+    /// Not fully paid, but the grace period is not over yet
+    Pending,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallmentOutcome {
+    pub due_timestamp: u64,
+    pub amount_due: u128,
+    pub amount_paid: u128,
+    /// Timestamp of the repayment that completed the installment
+    pub paid_at: Option<u64>,
+    pub status: InstallmentStatus,
+}
+
+/// Loan level view of the installment classification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LoanStatus {
+    /// Every installment paid on time
+    RepaidOnTime,
+    /// Fully repaid, but at least one installment was late
+    RepaidLate,
+    /// At least one installment was missed
+    Defaulted,
+    Liquidated,
+    /// Still running and no installment missed so far
+    Open,
+}
+
+/// Aggregated loan statuses of a borrower
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepaymentSummary {
+    pub repaid_on_time: u32,
+    pub repaid_late: u32,
+    pub defaulted: u32,
+    pub liquidated: u32,
+    pub open: u32,
+}
+
+impl RepaymentSummary {
+    /// Loans repaid on time are the positive history. Defaulted loans count as
+    /// liquidations: a borrower who stops paying must not end up with a cleaner history
+    /// than one who was liquidated. Late and open loans are not counted.
+    pub fn to_payment_history(&self) -> PaymentHistory {
+        PaymentHistory {
+            on_time_payments: self.repaid_on_time,
+            liquidations: self.liquidated.saturating_add(self.defaulted),
+        }
+    }
+}
+
+fn validate_terms(terms: &LoanTerms) -> Result<(), String> {
+    if terms.due_timestamps.is_empty() {
+        return Err("Loan must have at least one due date".to_string());
+    }
+
+    if terms.principal < terms.due_timestamps.len() as u128 {
+        return Err("Loan principal must cover every installment".to_string());
+    }
+
+    if terms.due_timestamps.windows(2).any(|w| w[0] >= w[1]) {
+        return Err("Loan due dates must be strictly increasing".to_string());
+    }
+
+    Ok(())
+}
+
+/// Amount due for each installment
+fn installment_amounts(terms: &LoanTerms) -> Vec<u128> {
+    let count = terms.due_timestamps.len() as u128;
+    let base = terms.principal / count;
+    let remainder = terms.principal % count;
+
+    let mut amounts = vec![base; terms.due_timestamps.len()];
+    if let Some(last) = amounts.last_mut() {
+        *last += remainder;
+    }
+    amounts
+}
+
+/// Classify every installment of a loan as of `as_of`.
+///
+/// Repayments are applied to the oldest outstanding installment first, an overpayment
+/// carries over to the next installment. A liquidation closes the loan: all installments
+/// that were not fully paid at that point are marked as liquidated and no further events
+/// are accepted.
+pub fn classify_installments(
+    terms: &LoanTerms,
+    events: &[LoanEvent],
+    as_of: u64,
+) -> Result<Vec<InstallmentOutcome>, String> {
+    validate_terms(terms)?;
+
+    if events
+        .windows(2)
+        .any(|w| w[0].timestamp() > w[1].timestamp())
+    {
+        return Err("Loan events must be ordered by timestamp".to_string());
+    }
+
+    if events.iter().any(|event| event.timestamp() > as_of) {
+        return Err("Loan events cannot be newer than the evaluation timestamp".to_string());
+    }
+
+    let mut outcomes: Vec<InstallmentOutcome> = terms
+        .due_timestamps
+        .iter()
+        .zip(installment_amounts(terms))
+        .map(|(&due_timestamp, amount_due)| InstallmentOutcome {
+            due_timestamp,
+            amount_due,
+            amount_paid: 0,
+            paid_at: None,
+            status: InstallmentStatus::Pending,
+        })
+        .collect();
+
+    let mut current = 0;
+    let mut liquidated_at = None;
+
+    for event in events {
+        if liquidated_at.is_some() {
+            return Err("Loan events after liquidation are not allowed".to_string());
+        }
+
+        match *event {
+            LoanEvent::Repayment { timestamp, amount } => {
+                let mut remaining = amount;
+                while remaining > 0 {
+                    let Some(installment) = outcomes.get_mut(current) else {
+                        return Err("Repayments exceed the loan principal".to_string());
+                    };
+
+                    let outstanding = installment.amount_due - installment.amount_paid;
+                    let applied = remaining.min(outstanding);
+                    installment.amount_paid += applied;
+                    remaining -= applied;
+
+                    if installment.amount_paid == installment.amount_due {
+                        installment.paid_at = Some(timestamp);
+                        current += 1;
+                    }
+                }
+            }
+            LoanEvent::Liquidation { timestamp } => liquidated_at = Some(timestamp),
+        }
+    }
+
+    for installment in outcomes.iter_mut() {
+        let deadline = installment.due_timestamp.saturating_add(terms.grace_period);
+
+        installment.status = match (installment.paid_at, liquidated_at) {
+            (Some(paid_at), _) if paid_at <= deadline => InstallmentStatus::OnTime,
+            (Some(_), _) => InstallmentStatus::Late,
+            (None, Some(_)) => InstallmentStatus::Liquidated,
+            (None, None) if as_of > deadline => InstallmentStatus::Missed,
+            (None, None) => InstallmentStatus::Pending,
+        };
+    }
+
+    Ok(outcomes)
+}
+
+/// Derive the loan level status from classified installments
+pub fn loan_status(outcomes: &[InstallmentOutcome]) -> LoanStatus {
+    let has = |status| outcomes.iter().any(|o| o.status == status);
+
+    if has(InstallmentStatus::Liquidated) {
+        LoanStatus::Liquidated
+    } else if has(InstallmentStatus::Missed) {
+        LoanStatus::Defaulted
+    } else if has(InstallmentStatus::Pending) {
+        LoanStatus::Open
+    } else if has(InstallmentStatus::Late) {
+        LoanStatus::RepaidLate
+    } else {
+        LoanStatus::RepaidOnTime
+    }
+}
+
+/// Classify all loans of a borrower and count them per status
+pub fn summarize_loans(
+    loans: &[(LoanTerms, Vec<LoanEvent>)],
+    as_of: u64,
+) -> Result<RepaymentSummary, String> {
+    let mut summary = RepaymentSummary::default();
+
+    for (terms, events) in loans {
+        let outcomes = classify_installments(terms, events, as_of)?;
+        match loan_status(&outcomes) {
+            LoanStatus::RepaidOnTime => summary.repaid_on_time += 1,
+            LoanStatus::RepaidLate => summary.repaid_late += 1,
+            LoanStatus::Defaulted => summary.defaulted += 1,
+            LoanStatus::Liquidated => summary.liquidated += 1,
+            LoanStatus::Open => summary.open += 1,
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Build the `PaymentHistory` consumed by `calculate_credit_score` from raw loan data
+pub fn build_payment_history(
+    loans: &[(LoanTerms, Vec<LoanEvent>)],
+    as_of: u64,
+) -> Result<PaymentHistory, String> {
+    Ok(summarize_loans(loans, as_of)?.to_payment_history())
+}
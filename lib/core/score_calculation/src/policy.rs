@@ -0,0 +1,263 @@
+use crate::{calculate_credit_score_with_policy, CreditInput, CreditScoreBreakdown};
+use ed25519_dalek::{Signature as Ed25519Signature, VerifyingKey as Ed25519VerifyingKey};
+use k256::ecdsa::{RecoveryId, Signature as EcdsaSignature, VerifyingKey as EcdsaVerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// Domain separator mixed into the policy digest, so a governance signature over a policy
+/// can never be replayed as a signature over something else.
+const POLICY_DOMAIN: &[u8] = b"risc_zero_banking.scoring_policy.v1";
+
+/// Tunable parameters of the score calculation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoringPolicy {
+    /// Version of the policy, bumped by governance on every change
+    pub version: u32,
+    /// Component weights in basis points, they have to add up to 10_000
+    pub payment_history_weight_bps: u16,
+    pub credit_utilization_weight_bps: u16,
+    pub tradify_integration_weight_bps: u16,
+    pub length_of_history_weight_bps: u16,
+    pub trust_factor_weight_bps: u16,
+    /// Account age (in days) at which the length of history score is maxed out
+    pub full_history_days: u64,
+    /// Payment history score for users without any loans
+    pub neutral_payment_score: u16,
+    /// Payment history penalty per liquidation
+    pub liquidation_penalty: u16,
+    /// Cap for the total liquidation penalty
+    pub max_liquidation_penalty: u16,
+    /// Tradify score for users without off-chain data
+    pub neutral_tradify_score: u16,
+}
+
+impl Default for ScoringPolicy {
+    /// Parameters the score calculation used before policies were configurable
+    fn default() -> Self {
+        Self {
+            version: 1,
+            payment_history_weight_bps: 3_000,
+            credit_utilization_weight_bps: 3_000,
+            tradify_integration_weight_bps: 1_500,
+            length_of_history_weight_bps: 1_500,
+            trust_factor_weight_bps: 1_000,
+            full_history_days: 730,
+            neutral_payment_score: 650,
+            liquidation_penalty: 25,
+            max_liquidation_penalty: 150,
+            neutral_tradify_score: 650,
+        }
+    }
+}
+
+impl ScoringPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        let total_weight = u32::from(self.payment_history_weight_bps)
+            + u32::from(self.credit_utilization_weight_bps)
+            + u32::from(self.tradify_integration_weight_bps)
+            + u32::from(self.length_of_history_weight_bps)
+            + u32::from(self.trust_factor_weight_bps);
+        if total_weight != 10_000 {
+            return Err("Scoring policy weights must add up to 10000 bps".to_string());
+        }
+
+        if self.full_history_days == 0 {
+            return Err("Scoring policy full history period must be greater than zero".to_string());
+        }
+
+        for score in [self.neutral_payment_score, self.neutral_tradify_score] {
+            if !(300..=850).contains(&score) {
+                return Err("Scoring policy neutral scores must be between 300-850".to_string());
+            }
+        }
+
+        if self.liquidation_penalty > self.max_liquidation_penalty {
+            return Err("Scoring policy liquidation penalty exceeds its cap".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Weight in basis points as a fraction
+    pub fn weight(&self, bps: u16) -> f64 {
+        bps as f64 / 10_000.0
+    }
+
+    /// Canonical encoding of the policy: domain separator followed by all fields in
+    /// declaration order, big endian. This is what the digest is computed over.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(POLICY_DOMAIN.len() + 32);
+        bytes.extend_from_slice(POLICY_DOMAIN);
+        bytes.extend_from_slice(&self.version.to_be_bytes());
+        bytes.extend_from_slice(&self.payment_history_weight_bps.to_be_bytes());
+        bytes.extend_from_slice(&self.credit_utilization_weight_bps.to_be_bytes());
+        bytes.extend_from_slice(&self.tradify_integration_weight_bps.to_be_bytes());
+        bytes.extend_from_slice(&self.length_of_history_weight_bps.to_be_bytes());
+        bytes.extend_from_slice(&self.trust_factor_weight_bps.to_be_bytes());
+        bytes.extend_from_slice(&self.full_history_days.to_be_bytes());
+        bytes.extend_from_slice(&self.neutral_payment_score.to_be_bytes());
+        bytes.extend_from_slice(&self.liquidation_penalty.to_be_bytes());
+        bytes.extend_from_slice(&self.max_liquidation_penalty.to_be_bytes());
+        bytes.extend_from_slice(&self.neutral_tradify_score.to_be_bytes());
+        bytes
+    }
+
+    /// keccak256 of the canonical encoding, this is what governance signs
+    pub fn digest(&self) -> [u8; 32] {
+        Keccak256::digest(self.canonical_bytes()).into()
+    }
+}
+
+/// Key allowed to sign scoring policies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GovernanceKey {
+    /// Ethereum address of a secp256k1 key, the signature is an EIP-191 `personal_sign`
+    /// over the policy digest, so any Ethereum wallet can sign policies
+    Secp256k1Address([u8; 20]),
+    /// Raw ed25519 public key, the signature is over the policy digest
+    Ed25519([u8; 32]),
+}
+
+/// What the verifier of a score trusts: the governance keys, and the oldest policy version
+/// it still accepts. Without the version floor, any older (possibly more lenient) policy
+/// ever signed by a pinned key could be replayed forever.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyPins {
+    pub keys: Vec<GovernanceKey>,
+    pub min_version: u32,
+}
+
+/// Governance signature over `ScoringPolicy::digest`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PolicySignature {
+    /// 65 bytes: r || s || v, with v being 27/28 or 0/1
+    Secp256k1(Vec<u8>),
+    /// 64 bytes
+    Ed25519(Vec<u8>),
+}
+
+/// Policy document as distributed by governance
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedScoringPolicy {
+    pub policy: ScoringPolicy,
+    pub signature: PolicySignature,
+}
+
+/// Identifies the approved policy a score was calculated with. Committed next to the
+/// breakdown so lenders can check it against the governance registry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyCommitment {
+    pub policy_digest: [u8; 32],
+    pub signer: GovernanceKey,
+}
+
+/// Score calculated with a verified policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyScoreOutput {
+    pub breakdown: CreditScoreBreakdown,
+    pub policy: PolicyCommitment,
+}
+
+/// EIP-191 hash of a 32 byte message, as produced by `personal_sign`
+fn eth_signed_message_hash(message: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(b"\x19Ethereum Signed Message:\n32");
+    hasher.update(message);
+    hasher.finalize().into()
+}
+
+fn recover_secp256k1_address(digest: &[u8; 32], signature: &[u8]) -> Result<[u8; 20], String> {
+    if signature.len() != 65 {
+        return Err("secp256k1 policy signature must be 65 bytes".to_string());
+    }
+
+    let ecdsa_signature = EcdsaSignature::from_slice(&signature[..64])
+        .map_err(|_| "Invalid secp256k1 policy signature".to_string())?;
+    // Reject malleable (high s) signatures
+    if ecdsa_signature.normalize_s().is_some() {
+        return Err("Non canonical secp256k1 policy signature".to_string());
+    }
+
+    let v = match signature[64] {
+        27 | 28 => signature[64] - 27,
+        0 | 1 => signature[64],
+        _ => return Err("Invalid secp256k1 recovery id".to_string()),
+    };
+    let recovery_id =
+        RecoveryId::from_byte(v).ok_or_else(|| "Invalid secp256k1 recovery id".to_string())?;
+
+    let verifying_key = EcdsaVerifyingKey::recover_from_prehash(
+        &eth_signed_message_hash(digest),
+        &ecdsa_signature,
+        recovery_id,
+    )
+    .map_err(|_| "Failed to recover policy signer".to_string())?;
+
+    // Ethereum address: last 20 bytes of keccak256(uncompressed public key without prefix)
+    let public_key = verifying_key.to_encoded_point(false);
+    let hash = Keccak256::digest(&public_key.as_bytes()[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    Ok(address)
+}
+
+fn verify_ed25519(digest: &[u8; 32], public_key: &[u8; 32], signature: &[u8]) -> bool {
+    let Ok(signature) = <[u8; 64]>::try_from(signature) else {
+        return false;
+    };
+    let Ok(verifying_key) = Ed25519VerifyingKey::from_bytes(public_key) else {
+        return false;
+    };
+
+    verifying_key
+        .verify_strict(digest, &Ed25519Signature::from_bytes(&signature))
+        .is_ok()
+}
+
+/// Check the governance signature of a policy against the pinned keys and return the
+/// commitment identifying it. Fails if no pinned key signed the policy, or if the policy
+/// is older than the pinned minimum version.
+pub fn verify_signed_policy(
+    signed: &SignedScoringPolicy,
+    pins: &PolicyPins,
+) -> Result<PolicyCommitment, String> {
+    signed.policy.validate()?;
+    if signed.policy.version < pins.min_version {
+        return Err("Scoring policy version is below the pinned minimum".to_string());
+    }
+    let digest = signed.policy.digest();
+
+    let signer = match &signed.signature {
+        PolicySignature::Secp256k1(signature) => {
+            let address = recover_secp256k1_address(&digest, signature)?;
+            pins.keys
+                .iter()
+                .find(|key| **key == GovernanceKey::Secp256k1Address(address))
+        }
+        PolicySignature::Ed25519(signature) => pins.keys.iter().find(|key| match key {
+            GovernanceKey::Ed25519(public_key) => verify_ed25519(&digest, public_key, signature),
+            _ => false,
+        }),
+    };
+
+    match signer {
+        Some(signer) => Ok(PolicyCommitment {
+            policy_digest: digest,
+            signer: *signer,
+        }),
+        None => Err("Scoring policy is not signed by a pinned governance key".to_string()),
+    }
+}
+
+/// Verify the policy and calculate the score with it. This is the entry point for guests:
+/// the returned output is what should be committed to the journal.
+pub fn calculate_credit_score_with_signed_policy(
+    input: &CreditInput,
+    signed: &SignedScoringPolicy,
+    pins: &PolicyPins,
+) -> Result<PolicyScoreOutput, String> {
+    let policy = verify_signed_policy(signed, pins)?;
+    let breakdown = calculate_credit_score_with_policy(input, &signed.policy)?;
+
+    Ok(PolicyScoreOutput { breakdown, policy })
+}
@@ -0,0 +1,172 @@
+use crate::ledger::ScoreRecord;
+use crate::{calculate_credit_score, CreditInput, CreditScoreBreakdown};
+use serde::{Deserialize, Serialize};
+
+/// Parameters of the optional trend adjustment
+/// NOTE: the default values are a first guess, to be discussed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendConfig {
+    /// Number of most recent observation steps taken into account
+    pub window: usize,
+    /// Bonus per score increase inside the window
+    pub improvement_bonus_per_step: u16,
+    /// Cap for the total improvement bonus
+    pub max_improvement_bonus: u16,
+    /// Score decrease between two observations that counts as a sharp drop
+    pub sharp_drop_points: u16,
+    /// Penalty for each sharp drop inside the window
+    pub sharp_drop_penalty: u16,
+    /// Relative debt increase (in basis points of the previous debt) that counts as a spike
+    pub debt_spike_ratio_bps: u32,
+    /// Absolute debt increase (in wei) below which a rise is never considered a spike
+    pub min_debt_spike_wei: u128,
+    /// Penalty for each debt spike inside the window
+    pub debt_spike_penalty: u16,
+    /// Cap for the total penalty
+    pub max_penalty: u16,
+}
+
+impl Default for TrendConfig {
+    fn default() -> Self {
+        Self {
+            window: 4,
+            improvement_bonus_per_step: 5,
+            max_improvement_bonus: 25,
+            sharp_drop_points: 50,
+            sharp_drop_penalty: 40,
+            debt_spike_ratio_bps: 10_000, // debt more than doubled
+            min_debt_spike_wei: 100_000_000_000_000_000, // 0.1 ETH
+            debt_spike_penalty: 30,
+            max_penalty: 100,
+        }
+    }
+}
+
+/// Result of the trend analysis
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrendAdjustment {
+    /// Score calculated from the current input only
+    pub base_score: u16,
+    pub improvement_bonus: u16,
+    pub drop_penalty: u16,
+    pub debt_spike_penalty: u16,
+    /// `base_score` with bonus and penalties applied, kept in the 300-850 range
+    pub adjusted_score: u16,
+}
+
+/// Observation used internally: (final score, debt)
+type Observation = (u16, u128);
+
+/// Calculate the trend adjustment for the current breakdown given the past observations
+/// of the same borrower (oldest first).
+pub fn calculate_trend_adjustment(
+    history: &[ScoreRecord],
+    input: &CreditInput,
+    breakdown: &CreditScoreBreakdown,
+    config: &TrendConfig,
+) -> TrendAdjustment {
+    const MIN_SCORE: u16 = 300;
+    const MAX_SCORE: u16 = 850;
+
+    let mut observations: Vec<Observation> = history
+        .iter()
+        .map(|record| (record.breakdown.final_score, record.current_debt))
+        .collect();
+    observations.push((breakdown.final_score, input.current_debt));
+
+    // Only the last `window` steps are relevant
+    let start = observations
+        .len()
+        .saturating_sub(config.window.saturating_add(1));
+    let recent = &observations[start..];
+
+    let mut rising_steps: u16 = 0;
+    let mut sharp_drops: u16 = 0;
+    let mut debt_spikes: u16 = 0;
+    let mut any_decrease = false;
+
+    for step in recent.windows(2) {
+        let (previous_score, previous_debt) = step[0];
+        let (score, debt) = step[1];
+
+        if score > previous_score {
+            rising_steps = rising_steps.saturating_add(1);
+        } else if score < previous_score {
+            any_decrease = true;
+            if previous_score - score >= config.sharp_drop_points {
+                sharp_drops = sharp_drops.saturating_add(1);
+            }
+        }
+
+        if is_debt_spike(previous_debt, debt, config) {
+            debt_spikes = debt_spikes.saturating_add(1);
+        }
+    }
+
+    // Improvement is only rewarded when it is steady: no decrease inside the window.
+    // The config is user supplied, so all of this saturates instead of overflowing.
+    let improvement_bonus = if any_decrease {
+        0
+    } else {
+        rising_steps
+            .saturating_mul(config.improvement_bonus_per_step)
+            .min(config.max_improvement_bonus)
+    };
+
+    let drop_penalty = sharp_drops.saturating_mul(config.sharp_drop_penalty);
+    let debt_spike_penalty = debt_spikes.saturating_mul(config.debt_spike_penalty);
+    let total_penalty = drop_penalty
+        .saturating_add(debt_spike_penalty)
+        .min(config.max_penalty);
+
+    let adjusted_score = breakdown
+        .final_score
+        .saturating_add(improvement_bonus)
+        .saturating_sub(total_penalty)
+        .clamp(MIN_SCORE, MAX_SCORE);
+
+    TrendAdjustment {
+        base_score: breakdown.final_score,
+        improvement_bonus,
+        drop_penalty,
+        debt_spike_penalty,
+        adjusted_score,
+    }
+}
+
+fn is_debt_spike(previous_debt: u128, debt: u128, config: &TrendConfig) -> bool {
+    if debt <= previous_debt {
+        return false;
+    }
+
+    let increase = debt - previous_debt;
+    if increase < config.min_debt_spike_wei {
+        return false;
+    }
+
+    // increase / previous_debt > ratio, without risking an overflow on the multiplication
+    let threshold = previous_debt.saturating_mul(u128::from(config.debt_spike_ratio_bps)) / 10_000;
+    increase > threshold
+}
+
+/// Calculate the credit score and apply the trend adjustment based on the borrower's ledger
+/// history. The returned breakdown keeps the base `final_score`, the adjusted score is in
+/// the `TrendAdjustment`. Only the breakdown belongs in the ledger: recording adjusted
+/// scores would feed the adjustment back into later trend windows.
+pub fn calculate_credit_score_with_trend(
+    input: &CreditInput,
+    history: &[ScoreRecord],
+    config: &TrendConfig,
+) -> Result<(CreditScoreBreakdown, TrendAdjustment), String> {
+    if history
+        .iter()
+        .any(|record| record.timestamp > input.current_timestamp)
+    {
+        return Err("Score history cannot contain records from the future".to_string());
+    }
+
+    let breakdown = calculate_credit_score(input)?;
+    let adjustment = calculate_trend_adjustment(history, input, &breakdown, config);
+
+    Ok((breakdown, adjustment))
+}
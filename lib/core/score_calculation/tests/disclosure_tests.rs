@@ -0,0 +1,209 @@
+use score_calculation::disclosure::*;
+use score_calculation::policy::*;
+use score_calculation::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::Signer;
+
+    const BLINDING: [u8; 32] = [42u8; 32];
+
+    fn input() -> CreditInput {
+        CreditInput {
+            first_interaction_timestamp: 1000000000,
+            current_timestamp: 1063152000, // ~2 years later
+            payment_history: PaymentHistory {
+                on_time_payments: 10,
+                liquidations: 0,
+            },
+            total_eth_balance: 10_000_000_000_000_000_000, // 10 ETH
+            current_debt: 1_000_000_000_000_000_000,       // 1 ETH debt
+            tradify_credit_score: Some(750),
+            trust_level: TrustLevel::Platinum,
+        }
+    }
+
+    fn governance() -> ed25519_dalek::SigningKey {
+        ed25519_dalek::SigningKey::from_bytes(&[7u8; 32])
+    }
+
+    fn signed_policy(policy: ScoringPolicy) -> SignedScoringPolicy {
+        SignedScoringPolicy {
+            signature: PolicySignature::Ed25519(
+                governance().sign(&policy.digest()).to_bytes().to_vec(),
+            ),
+            policy,
+        }
+    }
+
+    fn pins() -> PolicyPins {
+        PolicyPins {
+            keys: vec![GovernanceKey::Ed25519(
+                governance().verifying_key().to_bytes(),
+            )],
+            min_version: 1,
+        }
+    }
+
+    fn score() -> PolicyScoreOutput {
+        calculate_credit_score_with_signed_policy(
+            &input(),
+            &signed_policy(ScoringPolicy::default()),
+            &pins(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_threshold_disclosure() {
+        let score = calculate_credit_score(&input()).unwrap().final_score;
+
+        let met = calculate_credit_score_disclosed(
+            &input(),
+            &signed_policy(ScoringPolicy::default()),
+            &pins(),
+            DisclosureMode::Threshold { threshold: score },
+            &BLINDING,
+        )
+        .unwrap();
+        assert_eq!(
+            met.disclosure,
+            Disclosure::Threshold {
+                threshold: score,
+                met: true
+            }
+        );
+
+        let not_met = calculate_credit_score_disclosed(
+            &input(),
+            &signed_policy(ScoringPolicy::default()),
+            &pins(),
+            DisclosureMode::Threshold {
+                threshold: score + 1,
+            },
+            &BLINDING,
+        )
+        .unwrap();
+        assert_eq!(
+            not_met.disclosure,
+            Disclosure::Threshold {
+                threshold: score + 1,
+                met: false
+            }
+        );
+
+        // The commitment does not depend on the disclosure mode
+        assert_eq!(met.breakdown_commitment, not_met.breakdown_commitment);
+        assert_eq!(met.policy.policy_digest, ScoringPolicy::default().digest());
+    }
+
+    #[test]
+    fn test_disclosure_commits_to_the_policy() {
+        let lenient = ScoringPolicy {
+            version: 2,
+            liquidation_penalty: 0,
+            ..ScoringPolicy::default()
+        };
+        let output = calculate_credit_score_disclosed(
+            &input(),
+            &signed_policy(lenient.clone()),
+            &pins(),
+            DisclosureMode::Full,
+            &BLINDING,
+        )
+        .unwrap();
+        assert_eq!(output.policy.policy_digest, lenient.digest());
+        assert_eq!(output.policy.signer, pins().keys[0]);
+
+        // Policies that are not signed by a pinned key produce no disclosure
+        let mut forged = signed_policy(ScoringPolicy::default());
+        forged.policy = lenient;
+        assert!(calculate_credit_score_disclosed(
+            &input(),
+            &forged,
+            &pins(),
+            DisclosureMode::Full,
+            &BLINDING
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_band_disclosure() {
+        let mut score = score();
+
+        score.breakdown.final_score = 712;
+        let output = disclose(&score, DisclosureMode::Band { width: 50 }, &BLINDING).unwrap();
+        assert_eq!(
+            output.disclosure,
+            Disclosure::Band {
+                lower: 700,
+                upper: 749
+            }
+        );
+
+        // The last band is capped at 850
+        score.breakdown.final_score = 850;
+        let output = disclose(&score, DisclosureMode::Band { width: 100 }, &BLINDING).unwrap();
+        assert_eq!(
+            output.disclosure,
+            Disclosure::Band {
+                lower: 800,
+                upper: 850
+            }
+        );
+    }
+
+    #[test]
+    fn test_open_breakdown_commitment() {
+        let score = score();
+        let breakdown = score.breakdown.clone();
+        let output = disclose(
+            &score,
+            DisclosureMode::Threshold { threshold: 700 },
+            &BLINDING,
+        )
+        .unwrap();
+
+        let opening = BreakdownOpening {
+            breakdown: breakdown.clone(),
+            blinding: BLINDING,
+        };
+        assert!(verify_breakdown_opening(
+            &output.breakdown_commitment,
+            &opening
+        ));
+
+        // Claiming a different score does not open the commitment
+        let mut forged = opening.clone();
+        forged.breakdown.final_score += 1;
+        assert!(!verify_breakdown_opening(
+            &output.breakdown_commitment,
+            &forged
+        ));
+
+        let wrong_blinding = BreakdownOpening {
+            breakdown,
+            blinding: [7u8; 32],
+        };
+        assert!(!verify_breakdown_opening(
+            &output.breakdown_commitment,
+            &wrong_blinding
+        ));
+    }
+
+    #[test]
+    fn test_invalid_disclosure_parameters() {
+        let score = score();
+
+        assert!(disclose(
+            &score,
+            DisclosureMode::Threshold { threshold: 900 },
+            &BLINDING
+        )
+        .is_err());
+        assert!(disclose(&score, DisclosureMode::Band { width: 0 }, &BLINDING).is_err());
+        assert!(disclose(&score, DisclosureMode::Full, &[0u8; 32]).is_err());
+    }
+}
@@ -0,0 +1,192 @@
+use score_calculation::loan_schedule::*;
+use score_calculation::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 86400;
+    const ETH: u128 = 1_000_000_000_000_000_000;
+
+    // 3 ETH loan, paid back in 3 monthly installments with 3 days of grace
+    fn terms() -> LoanTerms {
+        LoanTerms {
+            principal: 3 * ETH,
+            due_timestamps: vec![
+                1000000000 + 30 * DAY,
+                1000000000 + 60 * DAY,
+                1000000000 + 90 * DAY,
+            ],
+            grace_period: 3 * DAY,
+        }
+    }
+
+    fn repayment(timestamp: u64, amount: u128) -> LoanEvent {
+        LoanEvent::Repayment { timestamp, amount }
+    }
+
+    fn statuses(outcomes: &[InstallmentOutcome]) -> Vec<InstallmentStatus> {
+        outcomes.iter().map(|o| o.status).collect()
+    }
+
+    #[test]
+    fn test_all_installments_on_time() {
+        let terms = terms();
+        let events = vec![
+            repayment(terms.due_timestamps[0], ETH),
+            repayment(terms.due_timestamps[1] + 2 * DAY, ETH), // inside the grace period
+            repayment(terms.due_timestamps[2] - DAY, ETH),
+        ];
+
+        let outcomes = classify_installments(&terms, &events, 1000000000 + 100 * DAY).unwrap();
+
+        assert_eq!(statuses(&outcomes), vec![InstallmentStatus::OnTime; 3]);
+        assert_eq!(loan_status(&outcomes), LoanStatus::RepaidOnTime);
+    }
+
+    #[test]
+    fn test_late_missed_and_pending_installments() {
+        let terms = terms();
+        // first installment paid after the grace period, second one never
+        let events = vec![repayment(terms.due_timestamps[0] + 4 * DAY, ETH)];
+
+        let outcomes = classify_installments(&terms, &events, 1000000000 + 70 * DAY).unwrap();
+
+        assert_eq!(
+            statuses(&outcomes),
+            vec![
+                InstallmentStatus::Late,
+                InstallmentStatus::Missed,
+                InstallmentStatus::Pending
+            ]
+        );
+        assert_eq!(loan_status(&outcomes), LoanStatus::Defaulted);
+    }
+
+    #[test]
+    fn test_prepayment_carries_over_to_next_installments() {
+        let terms = terms();
+        let events = vec![repayment(1000000000 + DAY, 2 * ETH + ETH / 2)];
+
+        let outcomes = classify_installments(&terms, &events, 1000000000 + 10 * DAY).unwrap();
+
+        assert_eq!(outcomes[1].paid_at, Some(1000000000 + DAY));
+        assert_eq!(outcomes[2].amount_paid, ETH / 2);
+        assert_eq!(outcomes[2].status, InstallmentStatus::Pending);
+        assert_eq!(loan_status(&outcomes), LoanStatus::Open);
+    }
+
+    #[test]
+    fn test_liquidation_marks_outstanding_installments() {
+        let terms = terms();
+        let events = vec![
+            repayment(terms.due_timestamps[0], ETH),
+            LoanEvent::Liquidation {
+                timestamp: terms.due_timestamps[0] + 10 * DAY,
+            },
+        ];
+
+        let outcomes = classify_installments(&terms, &events, 1000000000 + 100 * DAY).unwrap();
+
+        assert_eq!(
+            statuses(&outcomes),
+            vec![
+                InstallmentStatus::OnTime,
+                InstallmentStatus::Liquidated,
+                InstallmentStatus::Liquidated
+            ]
+        );
+        assert_eq!(loan_status(&outcomes), LoanStatus::Liquidated);
+    }
+
+    #[test]
+    fn test_invalid_events_are_rejected() {
+        let terms = terms();
+        let as_of = 1000000000 + 100 * DAY;
+
+        // unordered events
+        let unordered = vec![
+            repayment(1000000000 + 2 * DAY, ETH),
+            repayment(1000000000 + DAY, ETH),
+        ];
+        assert!(classify_installments(&terms, &unordered, as_of).is_err());
+
+        // overpaying the principal
+        let overpaid = vec![repayment(1000000000 + DAY, 4 * ETH)];
+        assert!(classify_installments(&terms, &overpaid, as_of).is_err());
+
+        // events after liquidation
+        let after_liquidation = vec![
+            LoanEvent::Liquidation {
+                timestamp: 1000000000 + DAY,
+            },
+            repayment(1000000000 + 2 * DAY, ETH),
+        ];
+        assert!(classify_installments(&terms, &after_liquidation, as_of).is_err());
+
+        // events from the future
+        let future = vec![repayment(as_of + 1, ETH)];
+        assert!(classify_installments(&terms, &future, as_of).is_err());
+    }
+
+    #[test]
+    fn test_build_payment_history() {
+        let terms = terms();
+        let as_of = 1000000000 + 100 * DAY;
+        let loans = vec![
+            (terms.clone(), vec![repayment(1000000000 + DAY, 3 * ETH)]),
+            (
+                terms.clone(),
+                vec![LoanEvent::Liquidation {
+                    timestamp: 1000000000 + 40 * DAY,
+                }],
+            ),
+            (terms.clone(), vec![]), // defaulted, counted as a liquidation
+        ];
+
+        let summary = summarize_loans(&loans, as_of).unwrap();
+        assert_eq!(summary.defaulted, 1);
+
+        let history = build_payment_history(&loans, as_of).unwrap();
+        assert_eq!(
+            history,
+            PaymentHistory {
+                on_time_payments: 1,
+                liquidations: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_default_lowers_the_score() {
+        let terms = terms();
+        let as_of = 1000000000 + 100 * DAY;
+        let repaid = (terms.clone(), vec![repayment(1000000000 + DAY, 3 * ETH)]);
+        let defaulted = (terms.clone(), vec![]);
+        let liquidated = (
+            terms.clone(),
+            vec![LoanEvent::Liquidation {
+                timestamp: 1000000000 + 40 * DAY,
+            }],
+        );
+
+        let score = |loans: &[(LoanTerms, Vec<LoanEvent>)]| {
+            let input = CreditInput {
+                first_interaction_timestamp: 1000000000,
+                current_timestamp: as_of,
+                payment_history: build_payment_history(loans, as_of).unwrap(),
+                total_eth_balance: 10 * ETH,
+                current_debt: ETH,
+                tradify_credit_score: None,
+                trust_level: TrustLevel::Premium,
+            };
+            calculate_credit_score(&input).unwrap().final_score
+        };
+
+        let clean = score(std::slice::from_ref(&repaid));
+        let with_default = score(&[repaid.clone(), defaulted]);
+        assert!(with_default < clean);
+        // Never paying is no better than being liquidated
+        assert!(with_default <= score(&[repaid, liquidated]));
+    }
+}
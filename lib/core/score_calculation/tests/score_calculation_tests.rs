@@ -1,3 +1,4 @@
+use score_calculation::policy::ScoringPolicy;
 use score_calculation::*;
 
 #[cfg(test)]
@@ -174,4 +175,35 @@ mod tests {
 
         assert!(calculate_credit_score(&invalid_credit_score_input).is_err());
     }
+
+    #[test]
+    fn test_score_contributions_add_up_to_final_score() {
+        let input = CreditInput {
+            first_interaction_timestamp: 1000000000,
+            current_timestamp: 1063152000, // ~2 years later
+            payment_history: PaymentHistory {
+                on_time_payments: 10,
+                liquidations: 0,
+            },
+            total_eth_balance: 10_000_000_000_000_000_000, // 10 ETH
+            current_debt: 1_000_000_000_000_000_000,       // 1 ETH debt
+            tradify_credit_score: Some(750),
+            trust_level: TrustLevel::Platinum,
+        };
+
+        let result = calculate_credit_score(&input).unwrap();
+        let contributions = calculate_score_contributions(&result);
+
+        let total = contributions.payment_history
+            + contributions.credit_utilization
+            + contributions.tradify_integration
+            + contributions.length_of_history
+            + contributions.trust_factor;
+        assert_eq!(total as u16, result.final_score);
+        let policy = ScoringPolicy::default();
+        assert_eq!(
+            contributions.payment_history,
+            850.0 * policy.weight(policy.payment_history_weight_bps)
+        );
+    }
 }
@@ -0,0 +1,219 @@
+use score_calculation::ledger::*;
+use score_calculation::trend::*;
+use score_calculation::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_ledger_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "score_ledger_{}_{}.jsonl",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn input(current_timestamp: u64, current_debt: u128) -> CreditInput {
+        CreditInput {
+            first_interaction_timestamp: 1000000000,
+            current_timestamp,
+            payment_history: PaymentHistory {
+                on_time_payments: 5,
+                liquidations: 0,
+            },
+            total_eth_balance: 10_000_000_000_000_000_000, // 10 ETH
+            current_debt,
+            tradify_credit_score: None,
+            trust_level: TrustLevel::Premium,
+        }
+    }
+
+    fn record(borrower: &str, timestamp: u64, final_score: u16, current_debt: u128) -> ScoreRecord {
+        ScoreRecord {
+            borrower: borrower.to_string(),
+            timestamp,
+            current_debt,
+            breakdown: CreditScoreBreakdown {
+                length_of_history_score: 600,
+                payment_history_score: 850,
+                credit_utilization_score: 850,
+                tradify_integration_score: 650,
+                trust_factor_score: 750,
+                final_score,
+            },
+        }
+    }
+
+    #[test]
+    fn test_file_ledger_roundtrip() {
+        let path = temp_ledger_path("roundtrip");
+        let mut ledger = FileScoreLedger::new(&path);
+
+        ledger.record(record("0xaaa", 100, 650, 0)).unwrap();
+        ledger.record(record("0xbbb", 150, 700, 0)).unwrap();
+        ledger.record(record("0xaaa", 200, 680, 0)).unwrap();
+
+        // A fresh handle reads the same data back from disk
+        let reopened = FileScoreLedger::new(&path);
+        let history = reopened.history("0xaaa").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].timestamp, 100);
+        assert_eq!(history[1].breakdown.final_score, 680);
+        assert_eq!(reopened.latest("0xbbb").unwrap().unwrap().timestamp, 150);
+        assert!(reopened.latest("0xccc").unwrap().is_none());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_file_ledger_rejects_out_of_order_records() {
+        let path = temp_ledger_path("ordering");
+        let mut ledger = FileScoreLedger::new(&path);
+
+        ledger.record(record("0xaaa", 200, 650, 0)).unwrap();
+        assert!(ledger.record(record("0xaaa", 200, 660, 0)).is_err());
+        assert!(ledger.record(record("0xaaa", 100, 660, 0)).is_err());
+        // Other borrowers are not affected
+        assert!(ledger.record(record("0xbbb", 100, 660, 0)).is_ok());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_trend_without_history_keeps_score() {
+        let current = input(1031536000, 0);
+        let (breakdown, adjustment) =
+            calculate_credit_score_with_trend(&current, &[], &TrendConfig::default()).unwrap();
+
+        assert_eq!(adjustment.base_score, adjustment.adjusted_score);
+        assert_eq!(
+            breakdown.final_score,
+            calculate_credit_score(&current).unwrap().final_score
+        );
+    }
+
+    #[test]
+    fn test_trend_rewards_steady_improvement() {
+        let current = input(1031536000, 0);
+        let base = calculate_credit_score(&current).unwrap().final_score;
+        let history = vec![
+            record("0xaaa", 1000100000, base - 30, 0),
+            record("0xaaa", 1000200000, base - 20, 0),
+            record("0xaaa", 1000300000, base - 10, 0),
+        ];
+
+        let (breakdown, adjustment) =
+            calculate_credit_score_with_trend(&current, &history, &TrendConfig::default()).unwrap();
+
+        assert_eq!(adjustment.improvement_bonus, 15);
+        assert_eq!(adjustment.adjusted_score, base + 15);
+        assert_eq!(breakdown.final_score, base);
+    }
+
+    #[test]
+    fn test_trend_penalizes_sharp_drop_and_debt_spike() {
+        // 0.1 ETH debt before, 4 ETH now
+        let current = input(1031536000, 4_000_000_000_000_000_000);
+        let base = calculate_credit_score(&current).unwrap().final_score;
+        let history = vec![record(
+            "0xaaa",
+            1000100000,
+            base + 80,
+            100_000_000_000_000_000,
+        )];
+
+        let (breakdown, adjustment) =
+            calculate_credit_score_with_trend(&current, &history, &TrendConfig::default()).unwrap();
+
+        assert_eq!(adjustment.improvement_bonus, 0);
+        assert_eq!(adjustment.drop_penalty, 40);
+        assert_eq!(adjustment.debt_spike_penalty, 30);
+        assert_eq!(adjustment.adjusted_score, (base - 70).max(300));
+        assert_eq!(breakdown.final_score, base);
+    }
+
+    #[test]
+    fn test_recorded_adjustments_do_not_compound() {
+        let path = temp_ledger_path("compound");
+        let mut ledger = FileScoreLedger::new(&path);
+        let config = TrendConfig::default();
+
+        // A borrower whose off-chain score improves before every observation
+        for step in 0..6u16 {
+            let mut current = input(1031536000 + u64::from(step) * 1000, 0);
+            current.tradify_credit_score = Some(600 + step * 40);
+            let base = calculate_credit_score(&current).unwrap().final_score;
+
+            let history = ledger.history("0xaaa").unwrap();
+            let (breakdown, adjustment) =
+                calculate_credit_score_with_trend(&current, &history, &config).unwrap();
+
+            // The bonus is bounded by the config, however many adjusted scores came before
+            assert_eq!(breakdown.final_score, base);
+            assert_eq!(adjustment.base_score, base);
+            assert!(adjustment.adjusted_score - base <= config.max_improvement_bonus);
+            if step > 0 {
+                assert!(adjustment.improvement_bonus > 0);
+            }
+
+            ledger
+                .record(ScoreRecord {
+                    borrower: "0xaaa".to_string(),
+                    timestamp: current.current_timestamp,
+                    current_debt: current.current_debt,
+                    breakdown,
+                })
+                .unwrap();
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_trend_saturates_on_extreme_config() {
+        let config = TrendConfig {
+            window: usize::MAX,
+            improvement_bonus_per_step: u16::MAX,
+            max_improvement_bonus: u16::MAX,
+            sharp_drop_points: 1,
+            sharp_drop_penalty: u16::MAX,
+            debt_spike_penalty: u16::MAX,
+            max_penalty: u16::MAX,
+            ..TrendConfig::default()
+        };
+        let current = input(1031536000, 4_000_000_000_000_000_000);
+        let base = calculate_credit_score(&current).unwrap().final_score;
+
+        let rising = vec![
+            record("0xaaa", 1000100000, base - 20, 4_000_000_000_000_000_000),
+            record("0xaaa", 1000200000, base - 10, 4_000_000_000_000_000_000),
+        ];
+        let (_, adjustment) =
+            calculate_credit_score_with_trend(&current, &rising, &config).unwrap();
+        assert_eq!(adjustment.improvement_bonus, u16::MAX);
+        assert_eq!(adjustment.adjusted_score, 850);
+
+        let dropping = vec![
+            record("0xaaa", 1000100000, base + 20, 0),
+            record("0xaaa", 1000200000, base + 10, 100_000_000_000_000_000),
+        ];
+        let (_, adjustment) =
+            calculate_credit_score_with_trend(&current, &dropping, &config).unwrap();
+        assert_eq!(adjustment.drop_penalty, u16::MAX);
+        assert_eq!(adjustment.adjusted_score, 300);
+    }
+
+    #[test]
+    fn test_trend_rejects_future_history() {
+        let current = input(1031536000, 0);
+        let history = vec![record("0xaaa", 1031536001, 700, 0)];
+
+        assert!(
+            calculate_credit_score_with_trend(&current, &history, &TrendConfig::default()).is_err()
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use score_calculation::policy::*;
+use score_calculation::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::Signer;
+    use sha3::{Digest, Keccak256};
+
+    // Anvil account #0
+    const ANVIL_PRIVATE_KEY_1: &str =
+        "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const ANVIL_ADDRESS_1: &str = "f39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+
+    fn input() -> CreditInput {
+        CreditInput {
+            first_interaction_timestamp: 1000000000,
+            current_timestamp: 1031536000, // 1 year later
+            payment_history: PaymentHistory {
+                on_time_payments: 4,
+                liquidations: 1,
+            },
+            total_eth_balance: 10_000_000_000_000_000_000, // 10 ETH
+            current_debt: 2_000_000_000_000_000_000,       // 2 ETH debt
+            tradify_credit_score: None,
+            trust_level: TrustLevel::Premium,
+        }
+    }
+
+    fn anvil_address() -> [u8; 20] {
+        hex::decode(ANVIL_ADDRESS_1).unwrap().try_into().unwrap()
+    }
+
+    fn pins(keys: &[GovernanceKey]) -> PolicyPins {
+        PolicyPins {
+            keys: keys.to_vec(),
+            min_version: 1,
+        }
+    }
+
+    // personal_sign over the policy digest, like `eth_utils::sign_message` does
+    fn sign_secp256k1(policy: &ScoringPolicy) -> PolicySignature {
+        let key = k256::ecdsa::SigningKey::from_slice(&hex::decode(ANVIL_PRIVATE_KEY_1).unwrap())
+            .unwrap();
+        let mut hasher = Keccak256::new();
+        hasher.update(b"\x19Ethereum Signed Message:\n32");
+        hasher.update(policy.digest());
+        let (signature, recovery_id) = key.sign_prehash_recoverable(&hasher.finalize()).unwrap();
+
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(27 + recovery_id.to_byte());
+        PolicySignature::Secp256k1(bytes)
+    }
+
+    fn lenient_policy() -> ScoringPolicy {
+        ScoringPolicy {
+            version: 2,
+            payment_history_weight_bps: 1_000,
+            credit_utilization_weight_bps: 1_000,
+            tradify_integration_weight_bps: 1_000,
+            length_of_history_weight_bps: 1_000,
+            trust_factor_weight_bps: 6_000,
+            liquidation_penalty: 0,
+            ..ScoringPolicy::default()
+        }
+    }
+
+    #[test]
+    fn test_default_policy_matches_calculate_credit_score() {
+        let default_score = calculate_credit_score(&input()).unwrap();
+        let policy_score =
+            calculate_credit_score_with_policy(&input(), &ScoringPolicy::default()).unwrap();
+
+        assert_eq!(default_score, policy_score);
+    }
+
+    #[test]
+    fn test_invalid_policy_is_rejected() {
+        let policy = ScoringPolicy {
+            trust_factor_weight_bps: 2_000, // weights add up to 110%
+            ..ScoringPolicy::default()
+        };
+
+        assert!(calculate_credit_score_with_policy(&input(), &policy).is_err());
+    }
+
+    #[test]
+    fn test_secp256k1_signed_policy() {
+        let signed = SignedScoringPolicy {
+            policy: ScoringPolicy::default(),
+            signature: sign_secp256k1(&ScoringPolicy::default()),
+        };
+        let pinned = [GovernanceKey::Secp256k1Address(anvil_address())];
+
+        let output =
+            calculate_credit_score_with_signed_policy(&input(), &signed, &pins(&pinned)).unwrap();
+
+        assert_eq!(
+            output.policy.policy_digest,
+            ScoringPolicy::default().digest()
+        );
+        assert_eq!(output.policy.signer, pinned[0]);
+        assert_eq!(output.breakdown, calculate_credit_score(&input()).unwrap());
+    }
+
+    #[test]
+    fn test_tampered_policy_is_rejected() {
+        // Governance signed the default policy, the prover swaps in a lenient one
+        let signed = SignedScoringPolicy {
+            policy: lenient_policy(),
+            signature: sign_secp256k1(&ScoringPolicy::default()),
+        };
+        let pinned = [GovernanceKey::Secp256k1Address(anvil_address())];
+
+        assert!(
+            calculate_credit_score_with_signed_policy(&input(), &signed, &pins(&pinned)).is_err()
+        );
+    }
+
+    #[test]
+    fn test_ed25519_signed_policy() {
+        let governance = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let other = ed25519_dalek::SigningKey::from_bytes(&[8u8; 32]);
+        let policy = lenient_policy();
+        let signed = SignedScoringPolicy {
+            signature: PolicySignature::Ed25519(
+                governance.sign(&policy.digest()).to_bytes().to_vec(),
+            ),
+            policy,
+        };
+
+        let pinned = [GovernanceKey::Ed25519(
+            governance.verifying_key().to_bytes(),
+        )];
+        let commitment = verify_signed_policy(&signed, &pins(&pinned)).unwrap();
+        assert_eq!(commitment.signer, pinned[0]);
+
+        // Same signature, but only an unrelated key is pinned
+        let wrong_pin = [GovernanceKey::Ed25519(other.verifying_key().to_bytes())];
+        assert!(verify_signed_policy(&signed, &pins(&wrong_pin)).is_err());
+    }
+
+    #[test]
+    fn test_outdated_policy_is_rejected() {
+        // Governance signed the lenient version 2 once, and has moved on to version 3
+        let policy = lenient_policy();
+        let signed = SignedScoringPolicy {
+            signature: sign_secp256k1(&policy),
+            policy,
+        };
+        let mut pins = pins(&[GovernanceKey::Secp256k1Address(anvil_address())]);
+
+        pins.min_version = 2;
+        assert!(verify_signed_policy(&signed, &pins).is_ok());
+        pins.min_version = 3;
+        assert!(verify_signed_policy(&signed, &pins).is_err());
+        assert!(calculate_credit_score_with_signed_policy(&input(), &signed, &pins).is_err());
+    }
+}
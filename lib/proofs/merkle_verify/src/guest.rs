@@ -3,15 +3,23 @@ use fetch_merkle::{UserHistory, UserHistoryProof};
 use score_calculation::CreditInput;
 // use serde::{Deserialize, Serialize};
 // use serde_json::{Value, json};
-use merkle_verifier_core::merkle_patricia::verify_eth_proof;
+use merkle_verifier_core::bundle::AccountProofBundle;
 pub struct AllMerkleProofs {
     pub user_history_proof: UserHistoryProof,
     pub owned_accounts_merkle_proofs: Vec<EIP1186ProofResponse>,
 }
 
-// TODO: 1. Convert the types and call verify_eth_proof
+// TODO: 1. Fetch the state root of the proof block and call `AccountProofBundle::verify`
 pub async fn verify_all_merkle_proofs(all_merkle_proofs: AllMerkleProofs) {
-    println!("{:?}", all_merkle_proofs.owned_accounts_merkle_proofs);
-    println!("{:?}", all_merkle_proofs.user_history_proof);
+    let user_history_bundle =
+        AccountProofBundle::from(&all_merkle_proofs.user_history_proof.merkle_proof);
+    let owned_account_bundles: Vec<AccountProofBundle> = all_merkle_proofs
+        .owned_accounts_merkle_proofs
+        .iter()
+        .map(AccountProofBundle::from)
+        .collect();
+
+    println!("{:?}", owned_account_bundles);
+    println!("{:?}", user_history_bundle);
     println!("yes");
 }
@@ -0,0 +1,89 @@
+use anyhow::{Context, Result};
+use ethereum_types::H256;
+use ethers::prelude::*;
+use host::{fetch_header_chain, setup_eth_provider, write_input, ProofInput};
+use merkle_verifier_core::block_header::header_hash;
+use merkle_verifier_core::bundle::AccountProofBundle;
+use merkle_verifier_core::framed::InputEncoding;
+use merkle_verifier_core::storage_layout::{address_key, mapping_slot};
+use methods::{ACCOUNT_MERKEL_PROOF_PATH, GUEST_KECCAK};
+use risc0_zkvm::{default_executor, ExecutorEnv};
+use std::fs;
+
+// Cycle count of the account proof guest for a typical mainnet account proof: the USDC
+// contract with the balance slot of one holder, anchored to the proven block itself.
+//
+// The guest runs once for every input encoding, to compare the risc0 serde codec with
+// the framed encoding. To measure the gain of the keccak accelerator, compare
+//   cargo run --release --bin cycles
+//   SOFTWARE_KECCAK=1 cargo run --release --bin cycles
+
+const BLOCK_NUMBER: u64 = 22406754;
+const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+// Slot of the `balanceOf` mapping of FiatTokenV2
+const USDC_BALANCES_SLOT: u64 = 9;
+const HOLDER: &str = "0x28C6c06298d514Db089934071355E5743bf21d60";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let provider = setup_eth_provider().await?;
+
+    let address = USDC.parse::<Address>()?;
+    let holder = HOLDER.parse::<Address>()?;
+    let slot = mapping_slot(
+        &address_key(&holder.0),
+        H256::from_low_u64_be(USDC_BALANCES_SLOT),
+    );
+
+    let headers = fetch_header_chain(&provider, BLOCK_NUMBER, BLOCK_NUMBER).await?;
+    let checkpoint_hash = header_hash(headers.last().context("Empty header chain")?);
+    let block = BlockId::Number(BlockNumber::Number(BLOCK_NUMBER.into()));
+    let proof = provider.get_proof(address, vec![slot], Some(block)).await?;
+    let account = AccountProofBundle::from(proof);
+
+    println!(
+        "Account proof: {} nodes, storage proof: {} nodes",
+        account.account_proof.len(),
+        account.storage_proofs[0].proof.len()
+    );
+
+    let input = ProofInput {
+        headers,
+        checkpoint_hash: checkpoint_hash.into(),
+        account,
+        prices: Vec::new(),
+        ocr1_code_hashes: Vec::new(),
+        staking: Vec::new(),
+    };
+
+    let elf_bytes = fs::read(ACCOUNT_MERKEL_PROOF_PATH)?;
+
+    println!("Keccak: {}", GUEST_KECCAK);
+
+    for encoding in [InputEncoding::Serde, InputEncoding::Framed] {
+        let mut exec_env = ExecutorEnv::builder();
+        write_input(&mut exec_env, &input, encoding)?;
+        let exec_env = exec_env.build()?;
+        let session = default_executor().execute(exec_env, &elf_bytes)?;
+
+        let input_size = match encoding {
+            InputEncoding::Serde => risc0_zkvm::serde::to_vec(&input)?.len() * 4,
+            InputEncoding::Framed => input.to_framed().len(),
+        };
+        let total_cycles: u64 = session
+            .segments
+            .iter()
+            .map(|segment| 1u64 << segment.po2)
+            .sum();
+
+        println!("{:?} input: {} bytes", encoding, input_size);
+        println!("  User cycles: {}", session.cycles());
+        println!(
+            "  Total cycles: {} in {} segments",
+            total_cycles,
+            session.segments.len()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use host::{fetch_age_proof, first_active_block, setup_eth_provider};
+use merkle_verifier_core::wallet_age::{
+    checkpoint_for, earliest_active, encode_age_proofs, ActiveSince, Checkpoint,
+    HISTORY_SERVE_WINDOW,
+};
+use methods::{WALLET_AGE_ID, WALLET_AGE_PATH};
+use risc0_zkvm::{default_prover, serde::from_slice, ExecutorEnv};
+use std::env;
+use std::fs;
+
+// Proves how long the owned accounts have been active: for every account the first block
+// with a nonce above zero.
+//
+//   cargo run --release --bin wallet_age -- 0xabc... 0xdef...
+//
+// Every block is anchored to a checkpoint at most HISTORY_SERVE_WINDOW blocks later: the
+// first one of the trusted checkpoints in the JSON file CHECKPOINTS (`[{number, hash}]`),
+// or the latest block for recent blocks, which verifiers check through EIP-2935. The
+// headers in between are part of the input. PROVEN_BLOCK moves the proven blocks later,
+// e.g. into the window of a checkpoint, at the price of a weaker lower bound.
+#[tokio::main]
+async fn main() -> Result<()> {
+    let provider = setup_eth_provider().await?;
+
+    let addresses = env::args()
+        .skip(1)
+        .map(|address| address.parse::<Address>())
+        .collect::<Result<Vec<_>, _>>()?;
+    anyhow::ensure!(!addresses.is_empty(), "No addresses given");
+
+    let latest = provider.get_block_number().await?.as_u64();
+    let checkpoints: Vec<Checkpoint> = match env::var("CHECKPOINTS") {
+        Ok(path) => serde_json::from_str(&fs::read_to_string(path)?)?,
+        Err(_) => Vec::new(),
+    };
+    let proven_block = env::var("PROVEN_BLOCK")
+        .ok()
+        .map(|number| number.parse::<u64>())
+        .transpose()?;
+
+    let mut proofs = Vec::new();
+    for address in addresses {
+        let first = first_active_block(&provider, address, latest)
+            .await?
+            .with_context(|| format!("{:?} has no transactions", address))?;
+        let number = proven_block.map_or(first, |block| block.max(first));
+        println!(
+            "{:?} active since block {}, proving block {}",
+            address, first, number
+        );
+
+        let checkpoint_number =
+            checkpoint_for(number, latest, &checkpoints).with_context(|| {
+                format!(
+                "Block {} is more than {} blocks old, set CHECKPOINTS to trusted historical hashes",
+                number, HISTORY_SERVE_WINDOW
+            )
+            })?;
+        let proof = fetch_age_proof(&provider, address, number, checkpoint_number).await?;
+        if let Some(checkpoint) = checkpoints.iter().find(|c| c.number == checkpoint_number) {
+            anyhow::ensure!(
+                checkpoint.hash.0 == proof.checkpoint_hash,
+                "Block {} does not match its trusted checkpoint",
+                checkpoint_number
+            );
+        }
+        proofs.push(proof);
+    }
+
+    let frame = encode_age_proofs(&proofs);
+    let prove_env = ExecutorEnv::builder()
+        .write_slice(&[u32::try_from(frame.len())?])
+        .write_slice(&frame)
+        .build()?;
+
+    println!("Reading ELF from: {}", WALLET_AGE_PATH);
+    let elf_bytes = fs::read(WALLET_AGE_PATH)?;
+    let receipt = default_prover().prove(prove_env, &elf_bytes)?.receipt;
+    receipt.verify(WALLET_AGE_ID)?;
+
+    let ages: Vec<ActiveSince> = from_slice(&receipt.journal.bytes)?;
+    for age in &ages {
+        println!(
+            "0x{} active since block {} (timestamp {}), nonce {} at that block",
+            hex::encode(age.address),
+            age.block_number,
+            age.timestamp,
+            age.nonce
+        );
+    }
+    if let Some(earliest) = earliest_active(&ages) {
+        println!(
+            "Wallet active since block {} (timestamp {}), checkpoint {} 0x{}",
+            earliest.block_number,
+            earliest.timestamp,
+            earliest.checkpoint_number,
+            hex::encode(earliest.checkpoint_hash.as_bytes())
+        );
+    }
+
+    fs::write("wallet_age_receipt.bin", bincode::serialize(&receipt)?)?;
+    Ok(())
+}
@@ -0,0 +1,311 @@
+use anyhow::{Context, Result};
+use ethereum_types::H256;
+use ethers::prelude::*;
+use merkle_verifier_core::block_header::{header_hash, BlockHeader};
+use merkle_verifier_core::bundle::AccountProofBundle;
+use merkle_verifier_core::framed::{FrameWriter, InputEncoding};
+use merkle_verifier_core::keccak::keccak256;
+use merkle_verifier_core::merkle_patricia::{decode_account, ProofVerification};
+use merkle_verifier_core::price_feed::{
+    aggregator_slots, decode_latest_round, decode_phase, proxy_slots, PriceProof,
+    AGGREGATOR_HOT_VARS_SLOT, PROXY_PHASE_SLOT,
+};
+use merkle_verifier_core::staking::{StakingProof, StakingToken};
+use merkle_verifier_core::trace::trace_proof;
+use merkle_verifier_core::wallet_age::AgeProof;
+use risc0_zkvm::ExecutorEnvBuilder;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::env;
+
+// Input of the account proof guest, matches the structure defined in the guest
+#[derive(Serialize, Deserialize)]
+pub struct ProofInput {
+    pub headers: Vec<Vec<u8>>,
+    pub checkpoint_hash: [u8; 32],
+    pub account: AccountProofBundle,
+    pub prices: Vec<PriceProof>,
+    // Aggregator code hashes to accept as OCR1, committed by the guest with the prices
+    pub ocr1_code_hashes: Vec<[u8; 32]>,
+    // Liquid staking tokens of the account, their ETH value is added to its balance
+    pub staking: Vec<StakingProof>,
+}
+
+impl ProofInput {
+    // Framed encoding of the input, the guest reads the fields in the same order
+    pub fn to_framed(&self) -> Vec<u8> {
+        let mut writer = FrameWriter::new();
+        writer
+            .list(&self.headers)
+            .fixed(&self.checkpoint_hash)
+            .bundle(&self.account)
+            .u32(u32::try_from(self.prices.len()).expect("Too many price proofs"));
+        for price in &self.prices {
+            price.write(&mut writer);
+        }
+        writer.u32(u32::try_from(self.ocr1_code_hashes.len()).expect("Too many code hashes"));
+        for code_hash in &self.ocr1_code_hashes {
+            writer.fixed(code_hash);
+        }
+        writer.u32(u32::try_from(self.staking.len()).expect("Too many staking proofs"));
+        for staking in &self.staking {
+            staking.write(&mut writer);
+        }
+        writer.finish()
+    }
+}
+
+// Write the guest input in the given encoding, preceded by the word that announces it
+pub fn write_input(
+    builder: &mut ExecutorEnvBuilder<'_>,
+    input: &ProofInput,
+    encoding: InputEncoding,
+) -> Result<()> {
+    builder.write_slice(&[encoding.tag()]);
+    match encoding {
+        InputEncoding::Serde => {
+            builder.write(input)?;
+        }
+        InputEncoding::Framed => {
+            let frame = input.to_framed();
+            builder
+                .write_slice(&[u32::try_from(frame.len())?])
+                .write_slice(&frame);
+        }
+    }
+    Ok(())
+}
+
+// Check the proofs on the host before handing them to the guest. If they do not verify,
+// the node level trace of every failing proof is printed: most of the time the RPC node
+// answered for another block than the header, or returned a truncated proof.
+pub fn check_account_proof(account: &AccountProofBundle, state_root: H256) -> Result<()> {
+    let Err(err) = account.verify(state_root) else {
+        return Ok(());
+    };
+
+    let address_hash = keccak256(&account.address);
+    let trace = trace_proof(state_root, address_hash.as_bytes(), &account.account_proof);
+    println!("Account 0x{}:\n{}", hex::encode(account.address), trace);
+
+    if let ProofVerification::Inclusion(account_rlp) = &trace.verification {
+        let storage_root = decode_account(account_rlp)
+            .map_err(|err| anyhow::anyhow!("Invalid account: {:?}", err))?
+            .storage_root;
+
+        for storage_proof in &account.storage_proofs {
+            let slot_hash = keccak256(&storage_proof.key);
+            let trace = trace_proof(storage_root, slot_hash.as_bytes(), &storage_proof.proof);
+            if let ProofVerification::Invalid(_) = trace.verification {
+                println!(
+                    "Storage slot 0x{}:\n{}",
+                    hex::encode(storage_proof.key),
+                    trace
+                );
+            }
+        }
+    }
+
+    anyhow::bail!("Proof does not verify against the state root: {:?}", err)
+}
+
+// Rebuild the consensus header of a block from the RPC representation
+pub fn block_header(block: &Block<H256>) -> Result<BlockHeader> {
+    Ok(BlockHeader {
+        parent_hash: block.parent_hash,
+        ommers_hash: block.uncles_hash,
+        beneficiary: block.author.context("Block without miner")?,
+        state_root: block.state_root,
+        transactions_root: block.transactions_root,
+        receipts_root: block.receipts_root,
+        logs_bloom: block.logs_bloom.context("Block without logs bloom")?,
+        difficulty: block.difficulty,
+        number: block.number.context("Pending block")?.as_u64(),
+        gas_limit: block.gas_limit.as_u64(),
+        gas_used: block.gas_used.as_u64(),
+        timestamp: block.timestamp.as_u64(),
+        extra_data: block.extra_data.to_vec(),
+        mix_hash: block.mix_hash.context("Block without mix hash")?,
+        nonce: block.nonce.context("Block without nonce")?,
+        base_fee_per_gas: block.base_fee_per_gas,
+        withdrawals_root: block.withdrawals_root,
+        blob_gas_used: block.blob_gas_used.map(|gas| gas.as_u64()),
+        excess_blob_gas: block.excess_blob_gas.map(|gas| gas.as_u64()),
+        parent_beacon_block_root: block.parent_beacon_block_root,
+        // Prague field, not known to ethers yet
+        requests_hash: block
+            .other
+            .get_deserialized::<H256>("requestsHash")
+            .transpose()?,
+    })
+}
+
+// RLP encoded headers from `from` up to and including `to`
+pub async fn fetch_header_chain(
+    provider: &Provider<Http>,
+    from: u64,
+    to: u64,
+) -> Result<Vec<Vec<u8>>> {
+    anyhow::ensure!(
+        from <= to,
+        "Checkpoint block is older than the proven block"
+    );
+
+    let mut headers = Vec::new();
+    for number in from..=to {
+        let block = provider
+            .get_block(BlockId::Number(BlockNumber::Number(number.into())))
+            .await?
+            .context("Block not found")?;
+        let header = block_header(&block)?;
+
+        // Catches header fields the conversion above does not know about
+        anyhow::ensure!(
+            Some(header.hash()) == block.hash,
+            "Rebuilt header of block {} does not match its hash",
+            number
+        );
+        headers.push(rlp::encode(&header).to_vec());
+    }
+
+    Ok(headers)
+}
+
+// First block at which `address` has a nonce above zero, at or before `latest`. Nonces
+// only grow, so a binary search over historical nonces finds it; needs an archive node.
+pub async fn first_active_block(
+    provider: &Provider<Http>,
+    address: Address,
+    latest: u64,
+) -> Result<Option<u64>> {
+    let nonce_at = |number: u64| {
+        provider.get_transaction_count(
+            address,
+            Some(BlockId::Number(BlockNumber::Number(number.into()))),
+        )
+    };
+    if nonce_at(latest).await?.is_zero() {
+        return Ok(None);
+    }
+
+    let (mut low, mut high) = (0, latest);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if nonce_at(mid).await?.is_zero() {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(Some(high))
+}
+
+// Account proof of `address` at block `number`, with the headers up to the checkpoint
+pub async fn fetch_age_proof(
+    provider: &Provider<Http>,
+    address: Address,
+    number: u64,
+    checkpoint_number: u64,
+) -> Result<AgeProof> {
+    let headers = fetch_header_chain(provider, number, checkpoint_number).await?;
+    let checkpoint_hash = header_hash(headers.last().context("Empty header chain")?);
+    let header = rlp::decode::<BlockHeader>(&headers[0])?;
+
+    let block = BlockId::Number(BlockNumber::Number(number.into()));
+    let account = AccountProofBundle::from(provider.get_proof(address, vec![], Some(block)).await?);
+    check_account_proof(&account, header.state_root)?;
+
+    Ok(AgeProof {
+        headers,
+        checkpoint_hash: checkpoint_hash.into(),
+        account,
+    })
+}
+
+// Proofs of the latest round of the Chainlink feed behind `proxy` at block `number`. The
+// aggregator and its latest round are read from storage first, to know which slots to
+// prove; the guest derives both again from the proven values.
+pub async fn fetch_price_proof(
+    provider: &Provider<Http>,
+    proxy: Address,
+    number: u64,
+) -> Result<PriceProof> {
+    let block = BlockId::Number(BlockNumber::Number(number.into()));
+    let storage = |address: Address, slot: u64| async move {
+        let value = provider
+            .get_storage_at(address, H256::from_low_u64_be(slot), Some(block))
+            .await?;
+        anyhow::Ok(U256::from_big_endian(value.as_bytes()))
+    };
+
+    let (_, aggregator) = decode_phase(storage(proxy, PROXY_PHASE_SLOT).await?)
+        .map_err(|err| anyhow::anyhow!("Invalid proxy phase: {:?}", err))?;
+    let aggregator = Address::from(aggregator);
+    let round = decode_latest_round(storage(aggregator, AGGREGATOR_HOT_VARS_SLOT).await?)
+        .map_err(|err| anyhow::anyhow!("Invalid aggregator state: {:?}", err))?;
+
+    let get_proof = |address: Address, slots: Vec<[u8; 32]>| async move {
+        let slots = slots.into_iter().map(H256::from).collect();
+        let proof = provider.get_proof(address, slots, Some(block)).await?;
+        anyhow::Ok(AccountProofBundle::from(proof))
+    };
+
+    Ok(PriceProof {
+        proxy: get_proof(proxy, proxy_slots()).await?,
+        aggregator: get_proof(aggregator, aggregator_slots(round)).await?,
+    })
+}
+
+// Balance proof of a liquid staking token held by `holder`, with the proof of the rate of
+// its protocol at the same block
+pub async fn fetch_staking_proof(
+    provider: &Provider<Http>,
+    token: StakingToken,
+    holder: Address,
+    number: u64,
+) -> Result<StakingProof> {
+    let block = BlockId::Number(BlockNumber::Number(number.into()));
+    let (token_slots, rate_slots) = token.required_slots(&holder.into());
+
+    let get_proof = |address: [u8; 20], slots: Vec<[u8; 32]>| async move {
+        let slots = slots.into_iter().map(H256::from).collect();
+        let proof = provider
+            .get_proof(Address::from(address), slots, Some(block))
+            .await?;
+        anyhow::Ok(AccountProofBundle::from(proof))
+    };
+
+    let rate = match rate_slots.is_empty() {
+        true => None,
+        false => Some(get_proof(token.rate_contract(), rate_slots).await?),
+    };
+    Ok(StakingProof {
+        token: get_proof(token.address(), token_slots).await?,
+        rate,
+    })
+}
+
+// Function to try multiple provider options
+pub async fn setup_eth_provider() -> Result<Provider<Http>> {
+    // Try Alchemy if environment variable exists
+    if let Ok(alchemy_key) = env::var("ALCHEMY_API_KEY") {
+        let url = format!("https://eth-mainnet.alchemyapi.io/v2/{}", alchemy_key);
+        let provider = Provider::<Http>::try_from(url.as_str())?;
+
+        // Test the connection
+        if let Ok(_) = provider.get_block_number().await {
+            println!("Connected to Ethereum via Alchemy");
+            return Ok(provider);
+        }
+    }
+    // If all else fails, try Ethereum archive RPCs from eth-archive.r2
+    let url = "https://eth-archive.r2.scorched.io/";
+    println!("Trying fallback archive node: {}", url);
+    let provider = Provider::<Http>::try_from(url)?;
+
+    // Test the connection
+    provider.get_block_number().await?;
+    println!("Connected to Ethereum via archive node");
+
+    Ok(provider)
+}
@@ -2,14 +2,23 @@ use anyhow::{Context, Result};
 use bincode;
 use ethereum_types::{H256, U256};
 use ethers::prelude::*;
+use host::{
+    check_account_proof, fetch_header_chain, fetch_price_proof, fetch_staking_proof,
+    setup_eth_provider, write_input, ProofInput,
+};
+use merkle_verifier_core::block_header::header_hash;
+use merkle_verifier_core::bundle::AccountProofBundle;
+use merkle_verifier_core::framed::InputEncoding;
+use merkle_verifier_core::price_feed::{ProvenPrice, FEED_PROFILES};
+use merkle_verifier_core::staking::{StakedBalance, StakingToken};
+use merkle_verifier_core::token::{token_profile, TOKEN_PROFILES};
 use risc0_groth16::docker::stark_to_snark;
 use risc0_zkvm::Prover;
 use risc0_zkvm::{
     default_executor, default_prover, serde::from_slice, ExecutorEnv, ProverOpts, Receipt,
     ReceiptKind,
 };
-use serde::{Deserialize, Serialize};
-use std::convert::TryFrom;
+use serde::Deserialize;
 use std::env;
 use std::fs;
 
@@ -18,23 +27,51 @@ use methods::ACCOUNT_MERKEL_PROOF_ID;
 use methods::ACCOUNT_MERKEL_PROOF_PATH;
 
 // Match the structures defined in the guest
-#[derive(Serialize, Deserialize)]
-struct ProofInput {
-    state_root: [u8; 32],
-    address: [u8; 20],
-    slot_key: Option<[u8; 32]>,
-    account_proof: Vec<Vec<u8>>,
-    storage_proof: Option<Vec<Vec<u8>>>,
-}
-
 #[derive(Deserialize)]
 struct ProofOutput {
+    checkpoint_hash: H256,
+    block_hash: H256,
+    block_number: u64,
     exists: bool,
     nonce: Option<U256>,
     balance: Option<U256>,
     storage_root: Option<H256>,
     code_hash: Option<H256>,
-    storage_value: Option<U256>,
+    storage_values: Vec<([u8; 32], U256)>,
+    prices: Vec<ProvenPrice>,
+    ocr1_code_hashes: Vec<H256>,
+    staked: Vec<StakedBalance>,
+}
+
+fn print_prices(output: &ProofOutput) {
+    for price in &output.prices {
+        let feed = FEED_PROFILES.iter().find(|feed| feed.proxy == price.feed);
+        println!(
+            "Price of {}: {} ({} decimals), round {} updated at {}",
+            feed.map_or("unknown feed", |feed| feed.description),
+            price.answer,
+            price.decimals,
+            price.round_id,
+            price.updated_at
+        );
+    }
+    for code_hash in &output.ocr1_code_hashes {
+        println!(
+            "Accepted OCR1 aggregator: 0x{}",
+            hex::encode(code_hash.as_bytes())
+        );
+    }
+}
+
+fn print_staked(output: &ProofOutput) {
+    for staked in &output.staked {
+        println!(
+            "Staked {}: {}, worth {} wei (included in the balance)",
+            token_profile(&staked.token).map_or("unknown token", |token| token.symbol),
+            staked.amount,
+            staked.eth
+        );
+    }
 }
 
 #[tokio::main]
@@ -61,46 +98,95 @@ async fn main() -> Result<()> {
     let state_root = block_data.state_root;
     println!("Got state root: 0x{}", hex::encode(state_root.as_bytes()));
 
+    // The guest only trusts the state root if the block links up to the checkpoint. The
+    // checkpoint defaults to the proven block itself; for an on-chain check use a block
+    // within the last 256 blocks, whose hash is available through `blockhash`.
+    let checkpoint_number = match env::var("CHECKPOINT_BLOCK") {
+        Ok(number) => number.parse::<u64>()?,
+        Err(_) => block_number,
+    };
+    let headers = fetch_header_chain(&provider, block_number, checkpoint_number).await?;
+    let checkpoint_hash = header_hash(headers.last().context("Empty header chain")?);
+    println!(
+        "Checkpoint block {}: 0x{}",
+        checkpoint_number,
+        hex::encode(checkpoint_hash.as_bytes())
+    );
+
     println!(
         "Getting proof for address: {} at block: {}",
         address, block_number
     );
 
     // Fetch the proof from the Ethereum node using eth_getProof
-    let mut proof = provider
+    let proof = provider
         .get_proof(address, vec![slot.unwrap_or_default()], Some(block))
         .await?;
 
     println!("Got proof, account has balance: {}", proof.balance);
 
-    // Convert proof to the format expected by our verifier
-    let account_proof: Vec<Vec<u8>> = proof
-        .account_proof
-        .into_iter()
-        .map(|bytes| bytes.to_vec())
-        .collect();
-
-    // Only include storage proof if we specified a slot
-    let storage_proof = if slot.is_some() {
-        Some(
-            proof.storage_proof[0]
-                .proof
-                .clone()
-                .into_iter()
-                .map(|bytes| bytes.to_vec())
-                .collect(),
-        )
-    } else {
-        None
+    // Every requested slot gets verified against the proven storage root
+    let account = AccountProofBundle::from(proof);
+    check_account_proof(&account, state_root)?;
+
+    // Chainlink feeds to prove at the same block, by description (e.g. "STETH / ETH"),
+    // and the code hashes of the aggregators the guest reads with the OCR1 layout
+    let ocr1_code_hashes = match env::var("OCR1_CODE_HASHES") {
+        Ok(list) => list
+            .split(',')
+            .map(|hash| hash.trim().parse::<H256>())
+            .collect::<Result<Vec<_>, _>>()?,
+        Err(_) => Vec::new(),
     };
+    let mut prices = Vec::new();
+    if let Ok(list) = env::var("PRICE_FEEDS") {
+        anyhow::ensure!(
+            !ocr1_code_hashes.is_empty(),
+            "PRICE_FEEDS needs the OCR1 aggregator code hashes in OCR1_CODE_HASHES"
+        );
+        for description in list.split(',').map(str::trim) {
+            let feed = FEED_PROFILES
+                .iter()
+                .find(|feed| feed.description == description)
+                .with_context(|| format!("Unknown price feed {}", description))?;
+            let price = fetch_price_proof(&provider, feed.proxy.into(), block_number).await?;
+
+            // Stale prices and aggregators of another layout would abort the guest
+            price
+                .verify(state_root, block_data.timestamp.as_u64(), &ocr1_code_hashes)
+                .map_err(|err| anyhow::anyhow!("{}: {:?}", description, err))?;
+            prices.push(price);
+        }
+    }
+
+    // Liquid staking tokens of the account, by symbol (e.g. "stETH,rETH"). Their ETH
+    // equivalent is added to the balance the guest commits.
+    let mut staking = Vec::new();
+    if let Ok(list) = env::var("STAKING_TOKENS") {
+        for symbol in list.split(',').map(str::trim) {
+            let token = TOKEN_PROFILES
+                .iter()
+                .find(|token| token.symbol == symbol)
+                .and_then(|token| StakingToken::from_address(&token.address))
+                .with_context(|| format!("Unknown liquid staking token {}", symbol))?;
+            let proof = fetch_staking_proof(&provider, token, address, block_number).await?;
+
+            let staked = proof
+                .verify(state_root, &address.into())
+                .map_err(|err| anyhow::anyhow!("{}: {:?}", symbol, err))?;
+            println!("Got proof, account holds {} {}", staked.amount, symbol);
+            staking.push(proof);
+        }
+    }
 
     // Prepare input for RISC Zero guest
     let input = ProofInput {
-        state_root: state_root.into(),
-        address: address.into(),
-        slot_key: slot.map(|h| h.into()),
-        account_proof,
-        storage_proof,
+        headers,
+        checkpoint_hash: checkpoint_hash.into(),
+        account,
+        prices,
+        ocr1_code_hashes: ocr1_code_hashes.into_iter().map(H256::into).collect(),
+        staking,
     };
 
     // Read the ELF file
@@ -109,7 +195,9 @@ async fn main() -> Result<()> {
 
     // For development: first run in the executor for faster debugging
     println!("Running executor for verification...");
-    let exec_env = ExecutorEnv::builder().write(&input)?.build()?;
+    let mut exec_env = ExecutorEnv::builder();
+    write_input(&mut exec_env, &input, InputEncoding::Framed)?;
+    let exec_env = exec_env.build()?;
 
     // Use the execute method with the ELF bytes
     let exec = default_executor();
@@ -120,6 +208,12 @@ async fn main() -> Result<()> {
 
     // Display the results from execution
     println!("Execution verification successful!");
+    println!(
+        "Block {} (0x{}) anchored to checkpoint 0x{}",
+        output.block_number,
+        hex::encode(output.block_hash.as_bytes()),
+        hex::encode(output.checkpoint_hash.as_bytes())
+    );
     println!("Account exists: {}", output.exists);
 
     if output.exists {
@@ -135,14 +229,18 @@ async fn main() -> Result<()> {
             hex::encode(output.code_hash.unwrap().as_bytes())
         );
 
-        if let Some(value) = output.storage_value {
-            println!("Storage value: {}", value);
+        for (slot, value) in &output.storage_values {
+            println!("Storage value at 0x{}: {}", hex::encode(slot), value);
         }
     }
+    print_prices(&output);
+    print_staked(&output);
 
     // Now generate an actual ZK proof (slower but cryptographically secure)
     println!("\nGenerating ZK proof...");
-    let prove_env = ExecutorEnv::builder().write(&input)?.build()?;
+    let mut prove_env = ExecutorEnv::builder();
+    write_input(&mut prove_env, &input, InputEncoding::Framed)?;
+    let prove_env = prove_env.build()?;
 
     let prover = default_prover();
     // Pass the ELF bytes
@@ -182,35 +280,12 @@ async fn main() -> Result<()> {
             hex::encode(output.code_hash.unwrap().as_bytes())
         );
 
-        if let Some(value) = output.storage_value {
-            println!("Storage value: {}", value);
+        for (slot, value) in &output.storage_values {
+            println!("Storage value at 0x{}: {}", hex::encode(slot), value);
         }
     }
+    print_prices(&output);
+    print_staked(&output);
 
     Ok(())
 }
-
-// Function to try multiple provider options
-async fn setup_eth_provider() -> Result<Provider<Http>> {
-    // Try Alchemy if environment variable exists
-    if let Ok(alchemy_key) = env::var("ALCHEMY_API_KEY") {
-        let url = format!("https://eth-mainnet.alchemyapi.io/v2/{}", alchemy_key);
-        let provider = Provider::<Http>::try_from(url.as_str())?;
-
-        // Test the connection
-        if let Ok(_) = provider.get_block_number().await {
-            println!("Connected to Ethereum via Alchemy");
-            return Ok(provider);
-        }
-    }
-    // If all else fails, try Ethereum archive RPCs from eth-archive.r2
-    let url = "https://eth-archive.r2.scorched.io/";
-    println!("Trying fallback archive node: {}", url);
-    let provider = Provider::<Http>::try_from(url)?;
-
-    // Test the connection
-    provider.get_block_number().await?;
-    println!("Connected to Ethereum via archive node");
-
-    Ok(provider)
-}
@@ -1,3 +1,21 @@
+use risc0_build::{embed_methods_with_options, GuestOptionsBuilder};
+use std::collections::HashMap;
+
 fn main() {
-    risc0_build::embed_methods();
+    // The guest hashes with the zkVM keccak accelerator. Setting SOFTWARE_KECCAK builds it
+    // with `sha3` instead, to compare both with `cargo run --bin cycles`.
+    println!("cargo:rerun-if-env-changed=SOFTWARE_KECCAK");
+    let (features, keccak) = match std::env::var("SOFTWARE_KECCAK") {
+        Ok(_) => (vec![], "sha3"),
+        Err(_) => (vec!["accelerated-keccak".to_string()], "accelerated"),
+    };
+    // Exported as `methods::GUEST_KECCAK`, so the host reports the backend that was built
+    // rather than the one of its own environment
+    println!("cargo:rustc-env=GUEST_KECCAK={}", keccak);
+
+    let options = GuestOptionsBuilder::default()
+        .features(features)
+        .build()
+        .unwrap();
+    embed_methods_with_options(HashMap::from([("account_merkel_proof", options)]));
 }
@@ -0,0 +1,27 @@
+#![no_std]
+#![no_main]
+extern crate alloc;
+use alloc::vec::Vec;
+use risc0_zkvm::guest::env;
+
+use merkle_verifier_core::wallet_age::{decode_age_proofs, verify_wallet_ages, ActiveSince};
+
+risc0_zkvm::guest::entry!(main);
+
+// Proves for every owned account that it was active (nonce > 0) at an early block. The
+// input is a single frame of `encode_age_proofs`, preceded by its length in bytes.
+pub fn main() {
+    let mut len = 0u32;
+    env::read_slice(core::slice::from_mut(&mut len));
+    let mut frame = alloc::vec![0u8; len as usize];
+    env::read_slice(&mut frame);
+
+    let proofs = decode_age_proofs(&frame).expect("Malformed framed input");
+
+    // Any account that was not active at its block aborts the guest, so a receipt only
+    // exists if every lower bound holds
+    let ages: Vec<ActiveSince> =
+        verify_wallet_ages(&proofs).expect("Account was not active at the proven block");
+
+    env::commit(&ages);
+}
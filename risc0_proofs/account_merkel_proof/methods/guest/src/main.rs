@@ -6,27 +6,99 @@ use ethereum_types::{H256, U256};
 use risc0_zkvm::guest::env;
 use serde::{Deserialize, Serialize};
 
-use merkle_verifier_core::merkle_patricia::{verify_eth_proof, AccountData};
+use merkle_verifier_core::block_header::{header_hash, verify_header_chain};
+use merkle_verifier_core::bundle::AccountProofBundle;
+use merkle_verifier_core::framed::{FrameReader, InputEncoding};
+use merkle_verifier_core::merkle_patricia::{AccountData, ProofError};
+use merkle_verifier_core::price_feed::{PriceProof, ProvenPrice};
+use merkle_verifier_core::staking::{total_eth_equivalent, StakedBalance, StakingProof};
 
 // Input structure
 #[derive(Deserialize, Serialize)]
 struct ProofInput {
-    state_root: [u8; 32],
-    address: [u8; 20],
-    slot_key: Option<[u8; 32]>,
-    account_proof: Vec<Vec<u8>>,
-    storage_proof: Option<Vec<Vec<u8>>>,
+    // RLP encoded headers from the proven block up to the checkpoint block
+    headers: Vec<Vec<u8>>,
+    // Trusted block hash, checked by the verifier of the receipt (e.g. against `blockhash`)
+    checkpoint_hash: [u8; 32],
+    account: AccountProofBundle,
+    // Chainlink feeds to prove at the same block
+    prices: Vec<PriceProof>,
+    // Code hashes of the aggregators known to have the OCR1 storage layout. They are
+    // committed with the prices, so the verifier of the receipt checks them against its
+    // pinned set like the checkpoint hash.
+    ocr1_code_hashes: Vec<[u8; 32]>,
+    // Liquid staking tokens held by the account
+    staking: Vec<StakingProof>,
+}
+
+impl ProofInput {
+    // Framed encoding, in the field order of the host's `ProofInput::to_framed`
+    fn from_framed(data: &[u8]) -> Result<Self, ProofError> {
+        let mut reader = FrameReader::new(data);
+        let headers = reader.list()?;
+        let checkpoint_hash = reader.fixed::<32>()?;
+        let account = reader.bundle()?;
+        let prices = (0..reader.u32()?)
+            .map(|_| PriceProof::read(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        let ocr1_code_hashes = (0..reader.u32()?)
+            .map(|_| reader.fixed::<32>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let staking = (0..reader.u32()?)
+            .map(|_| StakingProof::read(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let input = ProofInput {
+            headers,
+            checkpoint_hash,
+            account,
+            prices,
+            ocr1_code_hashes,
+            staking,
+        };
+        reader.finish()?;
+        Ok(input)
+    }
+}
+
+// The first word announces how the host encoded the rest of the input
+fn read_input() -> ProofInput {
+    let mut tag = 0u32;
+    env::read_slice(core::slice::from_mut(&mut tag));
+
+    match InputEncoding::from_tag(tag) {
+        Some(InputEncoding::Serde) => env::read(),
+        Some(InputEncoding::Framed) => {
+            let mut len = 0u32;
+            env::read_slice(core::slice::from_mut(&mut len));
+            let mut frame = alloc::vec![0u8; len as usize];
+            env::read_slice(&mut frame);
+            ProofInput::from_framed(&frame).expect("Malformed framed input")
+        }
+        None => panic!("Unknown input encoding"),
+    }
 }
 
 // Output structure
 #[derive(Serialize)]
 struct ProofOutput {
+    checkpoint_hash: H256,
+    block_hash: H256,
+    block_number: u64,
     exists: bool,
     nonce: Option<U256>,
+    // ETH balance of the account plus the ETH equivalent of its liquid staking tokens.
+    // `None` if the account does not exist and holds no staking tokens.
     balance: Option<U256>,
     storage_root: Option<H256>,
     code_hash: Option<H256>,
-    storage_value: Option<U256>,
+    // (slot, value) for every requested slot, absent slots hold zero
+    storage_values: Vec<([u8; 32], U256)>,
+    // Latest rounds of the requested feeds, fresh at the proven block
+    prices: Vec<ProvenPrice>,
+    ocr1_code_hashes: Vec<H256>,
+    // Liquid staking tokens included in `balance`
+    staked: Vec<StakedBalance>,
 }
 
 risc0_zkvm::guest::entry!(main);
@@ -35,59 +107,69 @@ risc0_zkvm::guest::entry!(main);
 // - inputs and outpust will change soon
 // - this is only suposed to show that the eth merkel proof verification works properly
 // TODO(likely):
-// 1. State root verification (hopfully state_root hash  can be signed by alchemy but this need in general some  investigation )
-// 2. Allowing for multiple account_proofs
-// 3. Adding the balances together and returning them as result
+// 1. Allowing for multiple account_proofs
+// 2. Adding the balances together and returning them as result
 pub fn main() {
     // Read the proof input
-    let input: ProofInput = env::read();
+    let input = read_input();
+
+    // The state root is only trusted if its block is an ancestor of the checkpoint. An
+    // invalid chain aborts the guest, so no receipt is produced for an untrusted root.
+    let checkpoint_hash = H256::from_slice(&input.checkpoint_hash);
+    let header = verify_header_chain(&input.headers, checkpoint_hash)
+        .expect("Block header chain does not lead to the checkpoint");
+    let block_hash = header_hash(&input.headers[0]);
 
-    // Convert the state root to H256
-    let state_root = H256::from_slice(&input.state_root);
+    // Verify the proof. An invalid proof aborts the guest like an invalid header chain: a
+    // receipt of non-existence is only produced for a proven exclusion.
+    let (account, storage_values) = input
+        .account
+        .verify(header.state_root)
+        .expect("Invalid account proof");
 
-    // Verify the proof
-    let result = verify_eth_proof(
-        state_root,
-        input.address,
-        input.slot_key,
-        input.account_proof,
-        input.storage_proof,
-    );
+    // Prices are proven at the same state root, and must be fresh at the block timestamp
+    let ocr1_code_hashes: Vec<H256> = input.ocr1_code_hashes.iter().map(H256::from).collect();
+    let prices = input
+        .prices
+        .iter()
+        .map(|price| price.verify(header.state_root, header.timestamp, &ocr1_code_hashes))
+        .collect::<Result<Vec<_>, _>>()
+        .expect("Invalid price proof");
+
+    // Staking tokens are held by the proven account, at the same state root
+    let staked = input
+        .staking
+        .iter()
+        .map(|staking| staking.verify(header.state_root, &input.account.address))
+        .collect::<Result<Vec<_>, _>>()
+        .expect("Invalid staking proof");
+    let staked_eth = total_eth_equivalent(&staked).expect("Invalid staked balances");
+    let balance = match (&account, staked_eth) {
+        (None, 0) => None,
+        (account, staked_eth) => {
+            let eth = account
+                .as_ref()
+                .map_or(U256::zero(), |account| account.balance);
+            Some(
+                eth.checked_add(U256::from(staked_eth))
+                    .expect("Balance overflows"),
+            )
+        }
+    };
 
-    // Process the result
-    let output = match result {
-        Ok((Some(account), storage_value)) => ProofOutput {
-            exists: true,
-            nonce: Some(account.nonce),
-            balance: Some(account.balance),
-            storage_root: Some(account.storage_root),
-            code_hash: Some(account.code_hash),
-            storage_value,
-        },
-        Ok((Some(account), _)) => ProofOutput {
-            exists: true,
-            nonce: Some(account.nonce),
-            balance: Some(account.balance),
-            storage_root: Some(account.storage_root),
-            code_hash: Some(account.code_hash),
-            storage_value: None,
-        },
-        Ok((None, _)) => ProofOutput {
-            exists: false,
-            nonce: None,
-            balance: None,
-            storage_root: None,
-            code_hash: None,
-            storage_value: None,
-        },
-        Err(_) => ProofOutput {
-            exists: false,
-            nonce: None,
-            balance: None,
-            storage_root: None,
-            code_hash: None,
-            storage_value: None,
-        },
+    let output = ProofOutput {
+        checkpoint_hash,
+        block_hash,
+        block_number: header.number,
+        exists: account.is_some(),
+        nonce: account.as_ref().map(|account| account.nonce),
+        balance,
+        storage_root: account.as_ref().map(|account| account.storage_root),
+        code_hash: account.as_ref().map(|account| account.code_hash),
+        storage_values: storage_values.into_iter().collect(),
+        prices,
+        ocr1_code_hashes,
+        staked,
     };
 
     // Commit the result
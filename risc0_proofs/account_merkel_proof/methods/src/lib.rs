@@ -3,3 +3,6 @@
 mod tests;
 
 include!(concat!(env!("OUT_DIR"), "/methods.rs"));
+
+// Keccak implementation the guest was built with, "accelerated" or "sha3"
+pub const GUEST_KECCAK: &str = env!("GUEST_KECCAK");